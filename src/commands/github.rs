@@ -14,16 +14,8 @@ use crate::config::ProxyConfig;
 use crate::core::command_registry::BotCommand;
 use crate::core::command_source::CommandSource;
 use crate::core::dispatcher::CommandDispatcher;
-
-const USAGE: &str = r#"用法：
-`/github user <username>`     # 查询用户信息
-`/github repo <owner>/<repo>` # 查询仓库信息
-`/github search <keyword> [--top=<num>] [--lang=<lang>] [--sort=stars|updated] [--order=desc|asc]` - 搜索仓库
-示例：
-`/github user GitHub`
-`/github repo Chiloven945/lukosbot2`
-`/github search lukosbot --top=5 --lang=java --sort=stars --order=desc`
-"#;
+use crate::i18n::tr;
+use crate::model::Segment;
 
 // -------------------- GitHub API types --------------------
 
@@ -43,13 +35,30 @@ struct GhUser {
 }
 
 #[derive(Debug, Deserialize)]
-struct GhRepo {
-    full_name: String,
-    html_url: String,
-    language: Option<String>,
-    stargazers_count: i64,
-    forks_count: i64,
-    description: Option<String>,
+pub struct GhRepo {
+    pub full_name: String,
+    pub html_url: String,
+    pub language: Option<String>,
+    pub stargazers_count: i64,
+    pub forks_count: i64,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GhIssueUser {
+    pub login: String,
+}
+
+/// Issue 与 PR 共用 issues 接口，PR 带有 `pull_request` 字段
+#[derive(Debug, Deserialize)]
+pub struct GhIssue {
+    pub number: i64,
+    pub title: String,
+    pub html_url: String,
+    pub state: String,
+    pub user: GhIssueUser,
+    pub comments: i64,
+    pub pull_request: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -91,10 +100,15 @@ impl GitHubApi {
         self.get_typed(&format!("/users/{username}"), &[]).await
     }
 
-    async fn get_repo(&self, owner: &str, repo: &str) -> Result<GhRepo> {
+    pub async fn get_repo(&self, owner: &str, repo: &str) -> Result<GhRepo> {
         self.get_typed(&format!("/repos/{owner}/{repo}"), &[]).await
     }
 
+    pub async fn get_issue(&self, owner: &str, repo: &str, number: u64) -> Result<GhIssue> {
+        self.get_typed(&format!("/repos/{owner}/{repo}/issues/{number}"), &[])
+            .await
+    }
+
     async fn search_repos(
         &self,
         keywords: &str,
@@ -120,7 +134,7 @@ impl GitHubApi {
             q.push(("order", order.trim().to_string()));
         }
         if per_page > 0 {
-            q.push(("per_page", per_page.min(10).to_string()));
+            q.push(("per_page", per_page.min(100).to_string()));
         }
 
         self.get_typed("/search/repositories", &q).await
@@ -173,13 +187,15 @@ pub struct GitHubCommand {
 }
 
 impl GitHubCommand {
-    pub fn new(token: Option<String>, proxy: &ProxyConfig) -> Self {
-        Self {
-            api: Arc::new(GitHubApi::new(token, proxy)),
-        }
+    const PAGE_SIZE: usize = 5;
+    /// 单次搜索最多取回的仓库数，超出一页的部分通过翻页查看
+    const MAX_TOP: usize = 50;
+
+    pub fn new(api: Arc<GitHubApi>) -> Self {
+        Self { api }
     }
 
-    async fn handle_user(api: &GitHubApi, username: &str) -> String {
+    async fn handle_user(api: &GitHubApi, username: &str, lang: &str) -> String {
         match api.get_user(username).await {
             Ok(u) => {
                 let display = u
@@ -188,9 +204,10 @@ impl GitHubCommand {
                     .filter(|s| !s.trim().is_empty())
                     .unwrap_or(u.login.as_str());
 
-                format!(
-                    "用户: {display} ({login})\n主页: {url}\n公开仓库: {repos} | 粉丝: {followers} | 关注: {following}\n",
-                    display = display,
+                tr!(
+                    lang,
+                    "github.user",
+                    name = display,
                     login = u.login,
                     url = u.html_url,
                     repos = u.public_repos,
@@ -200,44 +217,49 @@ impl GitHubCommand {
             }
             Err(e) => {
                 warn!("github user 查询失败: {username} err={e:?}");
-                format!("找不到用户或请求失败：{username}")
+                tr!(lang, "github.user-not-found", name = username)
             }
         }
     }
 
-    async fn handle_repo(api: &GitHubApi, repo_arg: &str) -> String {
+    async fn handle_repo(api: &GitHubApi, repo_arg: &str, lang: &str) -> String {
         let (owner, repo) = match repo_arg.split_once('/') {
             Some((a, b)) if !a.trim().is_empty() && !b.trim().is_empty() => (a.trim(), b.trim()),
-            _ => return "仓库格式应为 owner/repo".to_string(),
+            _ => return tr!(lang, "github.repo-format"),
         };
 
         match api.get_repo(owner, repo).await {
             Ok(r) => {
-                let lang = r.language.as_deref().unwrap_or("未知");
+                let language = r
+                    .language
+                    .clone()
+                    .unwrap_or_else(|| tr!(lang, "github.unknown-language"));
                 let desc = r
                     .description
-                    .as_deref()
+                    .clone()
                     .filter(|s| !s.trim().is_empty())
-                    .unwrap_or("无");
+                    .unwrap_or_else(|| tr!(lang, "github.no-description"));
 
-                format!(
-                    "仓库: {full_name}\n主页: {url}\n语言: {lang} | Star: {stars} | Fork: {forks}\n描述: {desc}\n",
-                    full_name = r.full_name,
+                tr!(
+                    lang,
+                    "github.repo",
+                    name = r.full_name,
                     url = r.html_url,
-                    lang = lang,
+                    language = language,
                     stars = r.stargazers_count,
                     forks = r.forks_count,
-                    desc = desc,
+                    description = desc,
                 )
             }
             Err(e) => {
                 warn!("github repo 查询失败: {repo_arg} err={e:?}");
-                format!("找不到仓库或请求失败：{repo_arg}")
+                tr!(lang, "github.repo-not-found", name = repo_arg)
             }
         }
     }
 
-    async fn handle_search(api: &GitHubApi, q: &str) -> String {
+    /// 每页 `PAGE_SIZE` 个仓库，由 `CommandSource::reply_pages` 负责翻页
+    async fn handle_search(api: &GitHubApi, q: &str, lang: &str) -> Vec<Vec<Segment>> {
         let p = Params::parse(q);
 
         match api
@@ -252,23 +274,28 @@ impl GitHubCommand {
         {
             Ok(resp) => {
                 if resp.items.is_empty() {
-                    return "未搜索到任何仓库。".to_string();
+                    return vec![vec![Segment::text(tr!(lang, "github.search-empty"))]];
                 }
 
                 let count = resp.items.len().min(p.top);
-                let mut sb = String::from("【仓库搜索结果】\n");
-                for repo in resp.items.iter().take(count) {
-                    sb.push_str(&repo.full_name);
-                    sb.push_str(" - ");
-                    sb.push_str(&format!("{}★\n", repo.stargazers_count));
-                    sb.push_str(&repo.html_url);
-                    sb.push_str("\n\n");
-                }
-                sb
+                resp.items[..count]
+                    .chunks(Self::PAGE_SIZE)
+                    .map(|repos| {
+                        let mut segs = vec![
+                            Segment::bold(tr!(lang, "github.search-title")),
+                            Segment::LineBreak,
+                        ];
+                        for repo in repos {
+                            segs.push(Segment::link(&repo.full_name, &repo.html_url));
+                            segs.push(Segment::text(format!(" - {}★\n\n", repo.stargazers_count)));
+                        }
+                        segs
+                    })
+                    .collect()
             }
             Err(e) => {
                 warn!("github search 失败: {q} err={e:?}");
-                format!("搜索失败：{e}")
+                vec![vec![Segment::text(tr!(lang, "github.search-failed", error = e))]]
             }
         }
     }
@@ -279,17 +306,32 @@ impl BotCommand for GitHubCommand {
         "github"
     }
 
-    fn description(&self) -> &'static str {
-        "GitHub 查询工具"
+    fn aliases(&self) -> &'static [&'static str] {
+        &["gh"]
     }
 
-    fn usage(&self) -> &'static str {
-        USAGE
+    /// 每次调用都会请求 GitHub API，限流从严
+    fn cost(&self) -> Option<u32> {
+        Some(2)
     }
 
-    fn register(&self, d: &mut CommandDispatcher<CommandSource>) {
-        let usage = self.usage().to_string();
+    fn cooldown(&self) -> Option<Duration> {
+        Some(Duration::from_secs(3))
+    }
+
+    fn description(&self, lang: &str) -> String {
+        tr!(lang, "github.description")
+    }
+
+    fn usage(&self, lang: &str) -> String {
+        tr!(lang, "github.usage")
+    }
 
+    fn timeout(&self) -> Option<Duration> {
+        Some(GitHubApi::CONN_TIMEOUT + GitHubApi::READ_TIMEOUT)
+    }
+
+    fn register(&self, d: &mut CommandDispatcher<CommandSource>) {
         let api_user = self.api.clone();
         let api_repo = self.api.clone();
         let api_search = self.api.clone();
@@ -301,10 +343,12 @@ impl BotCommand for GitHubCommand {
                         move |ctx: &CommandContext<CommandSource>| {
                             let username = get_string(ctx, "username").unwrap_or_default();
                             let src = ctx.source.clone();
+                            let lang = src.lang();
                             let api = api_user.clone();
 
-                            tokio::spawn(async move {
-                                let text = GitHubCommand::handle_user(api.as_ref(), &username).await;
+                            src.clone().spawn(async move {
+                                let text =
+                                    GitHubCommand::handle_user(api.as_ref(), &username, lang).await;
                                 src.reply(text);
                             });
 
@@ -317,10 +361,12 @@ impl BotCommand for GitHubCommand {
                         move |ctx: &CommandContext<CommandSource>| {
                             let repo_arg = get_string(ctx, "repo").unwrap_or_default();
                             let src = ctx.source.clone();
+                            let lang = src.lang();
                             let api = api_repo.clone();
 
-                            tokio::spawn(async move {
-                                let text = GitHubCommand::handle_repo(api.as_ref(), &repo_arg).await;
+                            src.clone().spawn(async move {
+                                let text =
+                                    GitHubCommand::handle_repo(api.as_ref(), &repo_arg, lang).await;
                                 src.reply(text);
                             });
 
@@ -333,11 +379,13 @@ impl BotCommand for GitHubCommand {
                         move |ctx: &CommandContext<CommandSource>| {
                             let query = get_string(ctx, "query").unwrap_or_default();
                             let src = ctx.source.clone();
+                            let lang = src.lang();
                             let api = api_search.clone();
 
-                            tokio::spawn(async move {
-                                let text = GitHubCommand::handle_search(api.as_ref(), &query).await;
-                                src.reply(text);
+                            src.clone().spawn(async move {
+                                let pages =
+                                    GitHubCommand::handle_search(api.as_ref(), &query, lang).await;
+                                src.reply_pages(pages);
                             });
 
                             1
@@ -345,7 +393,7 @@ impl BotCommand for GitHubCommand {
                     )),
                 )
                 .executes(move |ctx: &CommandContext<CommandSource>| {
-                    ctx.source.reply(usage.clone());
+                    ctx.source.reply_markdown(tr!(ctx.source.lang(), "github.usage"));
                     1
                 }),
         );
@@ -389,7 +437,7 @@ impl Params {
             .collect::<Vec<_>>()
             .join(" ");
 
-        let mut top = 3usize;
+        let mut top = 10usize;
         if let Some(v) = opts.get("top") {
             if let Ok(n) = v.parse::<usize>() {
                 top = n;
@@ -403,8 +451,8 @@ impl Params {
         };
 
         let top = match top {
-            0 => 3,
-            n => n.min(10),
+            0 => 10,
+            n => n.min(GitHubCommand::MAX_TOP),
         };
 
         Self {
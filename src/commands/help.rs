@@ -1,17 +1,21 @@
-use crate::config::AppProperties;
 use crate::core::command_registry::{BotCommand, CommandRegistry};
 use crate::core::command_source::CommandSource;
-use crate::core::dispatcher::{literal, CommandContext, CommandDispatcher};
-use std::sync::{Arc, Weak};
+use crate::core::command_tree::smart_usage;
+use crate::core::dispatcher::{
+    argument, get_string, literal, word, CommandContext, CommandDispatcher,
+};
+use crate::core::markup::parse_markdown;
+use crate::i18n::{self, tr};
+use crate::model::Segment;
+use std::sync::Weak;
 
 pub struct HelpCommand {
     registry: Weak<CommandRegistry>,
-    props: Arc<AppProperties>,
 }
 
 impl HelpCommand {
-    pub fn new(registry: Weak<CommandRegistry>, props: Arc<AppProperties>) -> Self {
-        Self { registry, props }
+    pub fn new(registry: Weak<CommandRegistry>) -> Self {
+        Self { registry }
     }
 }
 
@@ -20,38 +24,122 @@ impl BotCommand for HelpCommand {
         "help"
     }
 
-    fn description(&self) -> &'static str {
-        "列出可用命令或其详细用法"
+    fn description(&self, lang: &str) -> String {
+        tr!(lang, "help.description")
     }
 
-    fn usage(&self) -> &'static str {
-        "用法：\n`/help`           # 列出所有可用命令\n`/help <command>` # 显示指定命令的用法\n"
+    fn switchable(&self) -> bool {
+        false
+    }
+
+    fn usage(&self, lang: &str) -> String {
+        tr!(lang, "help.usage")
     }
 
     fn register(&self, dispatcher: &mut CommandDispatcher<CommandSource>) {
-        let props = self.props.clone();
         let registry = self.registry.clone();
+        let list_registry = registry.clone();
+
+        dispatcher.register(
+            literal("help")
+                .executes(move |ctx: &CommandContext<CommandSource>| {
+                    let lang = ctx.source.lang();
+                    let Some(reg) = list_registry.upgrade() else {
+                        ctx.source.reply(tr!(lang, "common.uninitialized"));
+                        return 1;
+                    };
+
+                    let mut segs = vec![Segment::text(tr!(lang, "help.title"))];
+                    let addr = &ctx.source.in_msg().addr;
+                    let prefix = reg.prefixes().primary(addr);
+                    for c in reg
+                        .all()
+                        .iter()
+                        .filter(|c| {
+                            c.visible()
+                                && c.permission() <= ctx.source.permission()
+                                && reg.is_enabled(addr, c.as_ref())
+                        })
+                    {
+                        segs.push(Segment::code(format!("{prefix}{}", c.name())));
+                        segs.push(Segment::text(format!(" - {}\n", c.description(lang))));
+                    }
+                    segs.push(Segment::LineBreak);
+                    let footer = tr!(lang, "help.footer");
+                    let (before, after) = i18n::split_at(&footer, "command");
+                    segs.push(Segment::text(before));
+                    segs.push(Segment::code(format!("{prefix}help <command>")));
+                    segs.push(Segment::text(after));
+                    ctx.source.reply_segments(segs);
+                    1
+                })
+                .then(argument("command", word()).executes(
+                    move |ctx: &CommandContext<CommandSource>| {
+                        let lang = ctx.source.lang();
+                        let Some(reg) = registry.upgrade() else {
+                            ctx.source.reply(tr!(lang, "common.uninitialized"));
+                            return 1;
+                        };
+
+                        let addr = &ctx.source.in_msg().addr;
+                        let prefix = reg.prefixes().primary(addr);
+                        let name = get_string(ctx, "command").unwrap_or_default();
+                        let name = reg.prefixes().strip_name(addr, &name);
+                        let Some(cmd) = reg
+                            .find(name)
+                            .filter(|c| {
+                                c.permission() <= ctx.source.permission()
+                                    && reg.is_enabled(addr, c.as_ref())
+                            })
+                        else {
+                            ctx.source.reply(tr!(
+                                lang,
+                                "help.unknown",
+                                name = name,
+                                help = format!("{prefix}help"),
+                            ));
+                            return 1;
+                        };
+
+                        let mut segs = command_help(cmd.as_ref(), &ctx.source, &prefix);
+                        let aliases = reg.aliases_of(cmd.name());
+                        if !aliases.is_empty() {
+                            segs.push(Segment::text(tr!(lang, "help.aliases")));
+                            for (i, a) in aliases.iter().enumerate() {
+                                if i > 0 {
+                                    segs.push(Segment::text(tr!(lang, "common.list-separator")));
+                                }
+                                segs.push(Segment::code(format!("{prefix}{a}")));
+                            }
+                        }
+                        ctx.source.reply_segments(segs);
+                        1
+                    },
+                )),
+        );
+    }
+}
+
+/// 命令说明 + 手写的 `usage()` + 由 brigadier 注册结构生成的语法树
+fn command_help(cmd: &dyn BotCommand, source: &CommandSource, prefix: &str) -> Vec<Segment> {
+    let lang = source.lang();
+    let mut segs = vec![
+        Segment::Bold(format!("{prefix}{}", cmd.name())),
+        Segment::text(format!(" - {}\n\n", cmd.description(lang))),
+    ];
+    segs.extend(parse_markdown(cmd.usage(lang).trim_end()));
 
-        dispatcher.register(literal("help").executes(
-            move |ctx: &CommandContext<CommandSource>| {
-                let Some(reg) = registry.upgrade() else {
-                    ctx.source.reply("命令系统未初始化。");
-                    return 1;
-                };
-
-                let mut sb = String::from("可用命令：\n");
-                for c in reg.all().iter().filter(|c| c.visible()) {
-                    sb.push_str(&format!(
-                        "{}{} - {}\n",
-                        props.prefix,
-                        c.name(),
-                        c.description()
-                    ));
-                }
-                sb.push_str("\n使用 `/help <command>` 查看具体命令的用法。");
-                ctx.source.reply(sb.trim().to_string());
-                1
-            },
-        ));
+    let tree = smart_usage(cmd, source);
+    if !tree.is_empty() {
+        segs.push(Segment::text(tr!(lang, "help.syntax")));
+        segs.push(Segment::CodeBlock {
+            lang: None,
+            code: tree
+                .iter()
+                .map(|l| format!("{prefix}{l}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        });
     }
+    segs
 }
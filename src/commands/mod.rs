@@ -1,3 +1,6 @@
 pub mod help;
 pub mod ping;
 pub mod github;
+pub mod prefix;
+pub mod settings;
+pub mod switch;
@@ -1,6 +1,7 @@
 use crate::core::command_registry::BotCommand;
 use crate::core::command_source::CommandSource;
 use crate::core::dispatcher::{literal, CommandContext, CommandDispatcher};
+use crate::i18n::tr;
 
 pub struct PingCommand;
 
@@ -8,11 +9,11 @@ impl BotCommand for PingCommand {
     fn name(&self) -> &'static str {
         "ping"
     }
-    fn description(&self) -> &'static str {
-        "Ping command"
+    fn description(&self, lang: &str) -> String {
+        tr!(lang, "ping.description")
     }
-    fn usage(&self) -> &'static str {
-        "ping"
+    fn usage(&self, lang: &str) -> String {
+        tr!(lang, "ping.usage")
     }
 
     fn register(&self, d: &mut CommandDispatcher<CommandSource>) {
@@ -0,0 +1,135 @@
+// src/commands/prefix.rs
+// 查看 / 设置当前会话的命令前缀
+use std::sync::{Arc, Weak};
+
+use crate::core::chat_settings::Layer;
+use crate::core::command_registry::{BotCommand, CommandRegistry};
+use crate::core::command_source::CommandSource;
+use crate::core::dispatcher::{
+    argument, get_string, greedy_string, literal, CommandContext, CommandDispatcher,
+};
+use crate::core::permission::Permission;
+use crate::core::prefix;
+use crate::i18n::tr;
+
+pub struct PrefixCommand {
+    registry: Weak<CommandRegistry>,
+}
+
+impl PrefixCommand {
+    pub fn new(registry: Weak<CommandRegistry>) -> Self {
+        Self { registry }
+    }
+
+    fn show(&self, ctx: &CommandContext<CommandSource>) -> i32 {
+        let lang = ctx.source.lang();
+        let Some(reg) = self.registry.upgrade() else {
+            ctx.source.reply(tr!(lang, "common.uninitialized"));
+            return 1;
+        };
+
+        let addr = &ctx.source.in_msg().addr;
+        let prefixes = reg.prefixes().of(addr);
+        let mut msg = if prefixes.is_empty() {
+            tr!(lang, "prefix.none")
+        } else {
+            tr!(lang, "prefix.current", prefixes = quoted(&prefixes, lang))
+        };
+        let layer = reg.settings().layer_of(addr, "prefixes");
+        if layer != Layer::Chat {
+            msg.push_str(&tr!(lang, "prefix.source", layer = layer.label(lang)));
+        }
+        if reg.prefixes().mention_enabled() {
+            msg.push_str(&tr!(lang, "prefix.mention-hint"));
+        }
+        ctx.source.reply(msg);
+        1
+    }
+
+    /// `None` 表示恢复为平台默认 / 全局配置
+    fn set(&self, ctx: &CommandContext<CommandSource>, prefixes: Option<Vec<String>>) -> i32 {
+        let lang = ctx.source.lang();
+        let Some(reg) = self.registry.upgrade() else {
+            ctx.source.reply(tr!(lang, "common.uninitialized"));
+            return 1;
+        };
+
+        let src = ctx.source.clone();
+        src.clone().spawn(async move {
+            let addr = &src.in_msg().addr;
+            let shown = prefixes.as_ref().map(|p| quoted(p, lang));
+            let res = reg
+                .settings()
+                .update(addr, |o| o.prefixes = prefixes)
+                .await;
+            match res {
+                Ok(()) => src.reply(match shown {
+                    Some(p) => tr!(lang, "prefix.set", prefixes = p),
+                    None => tr!(
+                        lang,
+                        "prefix.reset",
+                        prefixes = quoted(&reg.prefixes().of(addr), lang),
+                    ),
+                }),
+                Err(e) => src.reply(tr!(lang, "common.save-failed", error = e)),
+            }
+        });
+        1
+    }
+}
+
+fn quoted(prefixes: &[String], lang: &str) -> String {
+    prefixes
+        .iter()
+        .map(|p| tr!(lang, "prefix.quote", prefix = p))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl BotCommand for PrefixCommand {
+    fn name(&self) -> &'static str {
+        "prefix"
+    }
+
+    fn description(&self, lang: &str) -> String {
+        tr!(lang, "prefix.description")
+    }
+
+    fn usage(&self, lang: &str) -> String {
+        tr!(lang, "prefix.usage")
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::ChatAdmin
+    }
+
+    fn switchable(&self) -> bool {
+        false
+    }
+
+    fn register(&self, dispatcher: &mut CommandDispatcher<CommandSource>) {
+        let show = Arc::new(Self::new(self.registry.clone()));
+        let set = show.clone();
+        let reset = show.clone();
+
+        dispatcher.register(
+            literal("prefix")
+                .executes(move |ctx: &CommandContext<CommandSource>| show.show(ctx))
+                .then(literal("set").then(argument("prefixes", greedy_string()).executes(
+                    move |ctx: &CommandContext<CommandSource>| {
+                        let raw = get_string(ctx, "prefixes").unwrap_or_default();
+                        match prefix::parse_list(&raw, ctx.source.lang()) {
+                            Ok(p) => set.set(ctx, Some(p)),
+                            Err(msg) => {
+                                ctx.source.reply(msg);
+                                1
+                            }
+                        }
+                    },
+                )))
+                .then(literal("reset").executes(
+                    move |ctx: &CommandContext<CommandSource>| reset.set(ctx, None),
+                )),
+        );
+    }
+}
@@ -0,0 +1,256 @@
+// src/commands/settings.rs
+// 查看 / 修改当前会话的设置
+use std::sync::{Arc, Weak};
+
+use crate::core::chat_settings::{self, KEYS};
+use crate::core::command_registry::{BotCommand, CommandRegistry};
+use crate::core::command_source::CommandSource;
+use crate::core::dispatcher::{
+    argument, get_string, greedy_string, literal, word, CommandContext, CommandDispatcher,
+};
+use crate::core::permission::Permission;
+use crate::i18n::{self, tr};
+use crate::model::Address;
+
+pub struct SettingsCommand {
+    registry: Weak<CommandRegistry>,
+}
+
+impl SettingsCommand {
+    pub fn new(registry: Weak<CommandRegistry>) -> Self {
+        Self { registry }
+    }
+
+    fn show(&self, ctx: &CommandContext<CommandSource>, key: Option<&str>) -> i32 {
+        let lang = ctx.source.lang();
+        let Some(reg) = self.registry.upgrade() else {
+            ctx.source.reply(tr!(lang, "common.uninitialized"));
+            return 1;
+        };
+
+        let addr = &ctx.source.in_msg().addr;
+        match key {
+            Some(k) if !KEYS.contains(&k) => {
+                ctx.source.reply(chat_settings::unknown_key(k, lang))
+            }
+            Some(k) => ctx.source.reply(line(&reg, addr, k, lang)),
+            None => {
+                let lines: Vec<String> =
+                    KEYS.iter().map(|k| line(&reg, addr, k, lang)).collect();
+                ctx.source
+                    .reply(tr!(lang, "settings.title", lines = lines.join("\n")));
+            }
+        }
+        1
+    }
+
+    fn set(&self, ctx: &CommandContext<CommandSource>, key: String, raw: String) -> i32 {
+        let lang = ctx.source.lang();
+        let Some(reg) = self.registry.upgrade() else {
+            ctx.source.reply(tr!(lang, "common.uninitialized"));
+            return 1;
+        };
+
+        let addr = &ctx.source.in_msg().addr;
+        let raw = if key == "disabled-commands" {
+            match command_names(&reg, addr, &raw, lang) {
+                Ok(names) => names,
+                Err(msg) => {
+                    ctx.source.reply(msg);
+                    return 1;
+                }
+            }
+        } else {
+            raw
+        };
+
+        // 先在副本上校验，失败时不写入
+        let mut probe = reg.settings().overrides(addr);
+        if let Err(msg) = chat_settings::set_value(&mut probe, &key, &raw, lang) {
+            ctx.source.reply(msg);
+            return 1;
+        }
+
+        let src = ctx.source.clone();
+        src.clone().spawn(async move {
+            let addr = &src.in_msg().addr;
+            let res = reg
+                .settings()
+                .update(addr, |o| chat_settings::set_value(o, &key, &raw, lang))
+                .await;
+            // 刚修改了 language 时用新语言确认
+            let lang = reg.settings().language_for(src.in_msg());
+            match res {
+                Ok(_) => {
+                    src.reply(tr!(lang, "settings.updated", line = line(&reg, addr, &key, lang)))
+                }
+                Err(e) => src.reply(tr!(lang, "common.save-failed", error = e)),
+            }
+        });
+        1
+    }
+
+    fn reset(&self, ctx: &CommandContext<CommandSource>, key: Option<String>) -> i32 {
+        let lang = ctx.source.lang();
+        let Some(reg) = self.registry.upgrade() else {
+            ctx.source.reply(tr!(lang, "common.uninitialized"));
+            return 1;
+        };
+        if let Some(k) = key.as_deref().filter(|k| !KEYS.contains(k)) {
+            ctx.source.reply(chat_settings::unknown_key(k, lang));
+            return 1;
+        }
+
+        let src = ctx.source.clone();
+        src.clone().spawn(async move {
+            let addr = &src.in_msg().addr;
+            let res = reg
+                .settings()
+                .update(addr, |o| match key.as_deref() {
+                    Some(k) => chat_settings::clear_value(o, k),
+                    None => std::mem::take(o) != Default::default(),
+                })
+                .await;
+            let lang = reg.settings().language_for(src.in_msg());
+            match (res, key) {
+                (Ok(false), Some(k)) => src.reply(tr!(lang, "settings.key-not-set", key = k)),
+                (Ok(false), None) => src.reply(tr!(lang, "settings.nothing-set")),
+                (Ok(true), Some(k)) => {
+                    src.reply(tr!(lang, "settings.reset", line = line(&reg, addr, &k, lang)))
+                }
+                (Ok(true), None) => src.reply(tr!(lang, "settings.cleared")),
+                (Err(e), _) => src.reply(tr!(lang, "common.save-failed", error = e)),
+            }
+        });
+        1
+    }
+}
+
+/// `key = value（来源）`
+fn line(reg: &CommandRegistry, addr: &Address, key: &str, lang: &str) -> String {
+    let value = reg
+        .settings()
+        .get(addr)
+        .value_of(key)
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| tr!(lang, "settings.empty-value"));
+    let layer = reg.settings().layer_of(addr, key).label(lang);
+    tr!(lang, "settings.line", key = key, value = value, layer = layer)
+}
+
+/// 把命令名或别名换成正式名称，拒绝未知命令、不能关闭的命令和配置锁定的命令
+fn command_names(
+    reg: &CommandRegistry,
+    addr: &Address,
+    raw: &str,
+    lang: &str,
+) -> Result<String, String> {
+    if raw.trim() == "-" {
+        return Ok(String::new());
+    }
+    let mut names = vec![];
+    for name in raw.split_whitespace() {
+        let name = reg.prefixes().strip_name(addr, name);
+        let Some(cmd) = reg.find(name) else {
+            return Err(tr!(lang, "common.unknown-command", name = name));
+        };
+        if !cmd.switchable() {
+            return Err(tr!(lang, "common.cannot-disable", command = cmd.name()));
+        }
+        if reg.settings().is_locked(cmd.name()) {
+            return Err(tr!(lang, "switch.locked", command = cmd.name()));
+        }
+        names.push(cmd.name());
+    }
+    Ok(names.join(" "))
+}
+
+impl BotCommand for SettingsCommand {
+    fn name(&self) -> &'static str {
+        "settings"
+    }
+
+    fn description(&self, lang: &str) -> String {
+        tr!(lang, "settings.description")
+    }
+
+    fn usage(&self, lang: &str) -> String {
+        let locales: Vec<&str> = i18n::locales().collect();
+        tr!(lang, "settings.usage", locales = locales.join(" / "))
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::ChatAdmin
+    }
+
+    fn switchable(&self) -> bool {
+        false
+    }
+
+    fn register(&self, dispatcher: &mut CommandDispatcher<CommandSource>) {
+        let this = Arc::new(Self::new(self.registry.clone()));
+        let (show_all, get, set) = (this.clone(), this.clone(), this.clone());
+        let (reset_all, reset) = (this.clone(), this);
+
+        dispatcher.register(
+            literal("settings")
+                .executes(move |ctx: &CommandContext<CommandSource>| show_all.show(ctx, None))
+                .then(literal("get").then(argument("key", word()).executes(
+                    move |ctx: &CommandContext<CommandSource>| {
+                        let key = get_string(ctx, "key").unwrap_or_default();
+                        get.show(ctx, Some(&key.to_lowercase()))
+                    },
+                )))
+                .then(literal("set").then(argument("key", word()).then(
+                    argument("value", greedy_string()).executes(
+                        move |ctx: &CommandContext<CommandSource>| {
+                            let key = get_string(ctx, "key").unwrap_or_default();
+                            let value = get_string(ctx, "value").unwrap_or_default();
+                            set.set(ctx, key.to_lowercase(), value)
+                        },
+                    ),
+                )))
+                .then(
+                    literal("reset")
+                        .executes(move |ctx: &CommandContext<CommandSource>| {
+                            reset_all.reset(ctx, None)
+                        })
+                        .then(argument("key", word()).executes(
+                            move |ctx: &CommandContext<CommandSource>| {
+                                let key = get_string(ctx, "key").unwrap_or_default();
+                                reset.reset(ctx, Some(key.to_lowercase()))
+                            },
+                        )),
+                ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{AppProperties, ChatOverrides, ReplyMode};
+    use crate::core::command_processor::TestBot;
+    use crate::model::{Address, ChatPlatform, MessageIn};
+
+    /// QQ 私聊中用户是自己会话的管理员，但不能借同号改到群的设置
+    #[tokio::test]
+    async fn private_chat_cannot_change_group_with_same_number() {
+        let bot = TestBot::new(AppProperties::default()).await;
+        let settings = &bot.settings;
+
+        let dm = Address::new(ChatPlatform::Onebot, 123456i64, false);
+        let group = Address::new(ChatPlatform::Onebot, 123456i64, true);
+        for line in ["/switch off ping", "/settings set reply always", "/prefix set #"] {
+            let input = MessageIn::new(dm.clone(), 123456i64, line.to_string());
+            let outs = bot.processor.handle(input).await;
+            assert!(!outs.is_empty(), "{line} produced no reply");
+        }
+
+        assert_eq!(settings.get(&dm).prefixes, vec!["#"]);
+        assert_eq!(settings.get(&dm).reply, ReplyMode::Always);
+        assert!(settings.get(&dm).disabled_commands.contains("ping"));
+        assert_eq!(settings.get(&group).prefixes, vec!["/"]);
+        assert!(settings.get(&group).disabled_commands.is_empty());
+        assert_eq!(settings.overrides(&group), ChatOverrides::default());
+    }
+}
@@ -0,0 +1,169 @@
+// src/commands/switch.rs
+// 在当前会话中开启 / 关闭命令
+use std::sync::{Arc, Weak};
+
+use crate::core::command_registry::{BotCommand, CommandRegistry};
+use crate::core::command_source::CommandSource;
+use crate::core::dispatcher::{
+    argument, get_string, literal, word, CommandContext, CommandDispatcher,
+};
+use crate::core::permission::Permission;
+use crate::i18n::tr;
+
+pub struct SwitchCommand {
+    registry: Weak<CommandRegistry>,
+}
+
+impl SwitchCommand {
+    pub fn new(registry: Weak<CommandRegistry>) -> Self {
+        Self { registry }
+    }
+
+    fn set(&self, ctx: &CommandContext<CommandSource>, enabled: bool) -> i32 {
+        let lang = ctx.source.lang();
+        let Some(reg) = self.registry.upgrade() else {
+            ctx.source.reply(tr!(lang, "common.uninitialized"));
+            return 1;
+        };
+
+        let addr = &ctx.source.in_msg().addr;
+        let prefix = reg.prefixes().primary(addr);
+        let name = get_string(ctx, "command").unwrap_or_default();
+        let name = reg.prefixes().strip_name(addr, &name);
+        let Some(cmd) = reg.find(name) else {
+            ctx.source.reply(tr!(lang, "common.unknown-command", name = name));
+            return 1;
+        };
+        if !cmd.switchable() {
+            ctx.source.reply(tr!(
+                lang,
+                "common.cannot-disable",
+                command = format!("{prefix}{}", cmd.name()),
+            ));
+            return 1;
+        }
+        if reg.settings().is_locked(cmd.name()) {
+            ctx.source.reply(tr!(
+                lang,
+                "switch.locked",
+                command = format!("{prefix}{}", cmd.name()),
+            ));
+            return 1;
+        }
+
+        let name = cmd.name();
+        let src = ctx.source.clone();
+        src.clone().spawn(async move {
+            let addr = &src.in_msg().addr;
+            let settings = reg.settings();
+            // 在写锁内读取当前生效的列表（可能来自平台默认）再修改，并发的 /switch 不会互相覆盖
+            let res = settings
+                .update(addr, |o| {
+                    let mut disabled = settings.merge(addr, o.clone()).disabled_commands;
+                    if enabled {
+                        disabled.remove(name);
+                    } else {
+                        disabled.insert(name.to_string());
+                    }
+                    o.disabled_commands = Some(disabled.into_iter().collect());
+                })
+                .await;
+            match res {
+                Ok(()) => src.reply(tr!(
+                    lang,
+                    if enabled { "switch.enabled" } else { "switch.disabled" },
+                    command = format!("{prefix}{name}"),
+                )),
+                Err(e) => src.reply(tr!(lang, "common.save-failed", error = e)),
+            }
+        });
+        1
+    }
+}
+
+impl BotCommand for SwitchCommand {
+    fn name(&self) -> &'static str {
+        "switch"
+    }
+
+    fn description(&self, lang: &str) -> String {
+        tr!(lang, "switch.description")
+    }
+
+    fn usage(&self, lang: &str) -> String {
+        tr!(lang, "switch.usage")
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::ChatAdmin
+    }
+
+    fn switchable(&self) -> bool {
+        false
+    }
+
+    fn register(&self, dispatcher: &mut CommandDispatcher<CommandSource>) {
+        let list_registry = self.registry.clone();
+        let on = Arc::new(Self::new(self.registry.clone()));
+        let off = on.clone();
+
+        dispatcher.register(
+            literal("switch")
+                .executes(move |ctx: &CommandContext<CommandSource>| {
+                    let lang = ctx.source.lang();
+                    let Some(reg) = list_registry.upgrade() else {
+                        ctx.source.reply(tr!(lang, "common.uninitialized"));
+                        return 1;
+                    };
+                    let addr = &ctx.source.in_msg().addr;
+                    let disabled = reg.settings().get(addr).disabled_commands;
+                    if disabled.is_empty() {
+                        ctx.source.reply(tr!(lang, "switch.none-disabled"));
+                    } else {
+                        let prefix = reg.prefixes().primary(addr);
+                        let names: Vec<String> =
+                            disabled.iter().map(|n| format!("{prefix}{n}")).collect();
+                        let sep = tr!(lang, "common.list-separator");
+                        ctx.source
+                            .reply(tr!(lang, "switch.disabled-list", commands = names.join(&sep)));
+                    }
+                    1
+                })
+                .then(literal("on").then(argument("command", word()).executes(
+                    move |ctx: &CommandContext<CommandSource>| on.set(ctx, true),
+                )))
+                .then(literal("off").then(argument("command", word()).executes(
+                    move |ctx: &CommandContext<CommandSource>| off.set(ctx, false),
+                ))),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::AppProperties;
+    use crate::core::command_processor::TestBot;
+    use crate::model::{Address, ChatPlatform, MessageIn};
+
+    /// 同时关闭两个命令，两次修改都应保留
+    #[tokio::test]
+    async fn concurrent_switches_keep_both_changes() {
+        let bot = TestBot::new(AppProperties::default()).await;
+
+        let dm = Address::new(ChatPlatform::Onebot, 42i64, false);
+        let tasks: Vec<_> = ["ping", "github"]
+            .into_iter()
+            .map(|name| {
+                let processor = bot.processor.clone();
+                let input = MessageIn::new(dm.clone(), 42i64, format!("/switch off {name}"));
+                tokio::spawn(async move { processor.handle(input).await })
+            })
+            .collect();
+        for t in tasks {
+            assert!(!t.await.unwrap().is_empty());
+        }
+
+        let disabled = bot.settings.get(&dm).disabled_commands;
+        assert!(disabled.contains("ping") && disabled.contains("github"));
+    }
+}
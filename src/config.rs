@@ -1,49 +1,118 @@
 use anyhow::{anyhow, Context, Result};
-use serde::{Deserialize, Serialize};
-use std::{fs, path::Path};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{collections::HashMap, fs, path::Path};
 
 use config::{Config, Environment, File};
 use regex::Regex;
 use url::Url;
 
+use crate::model::{Address, ChatPlatform};
+
 const CONFIG_FILE: &str = "config/application.toml";
 const TEMPLATE_TOML: &str = include_str!("../resources/application.example.toml");
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct AppProperties {
-    pub prefix: String,
+    /// 可接受的命令前缀，各会话可用 `/prefix` 覆盖；兼容旧版的 `prefix = "/"`
+    #[serde(alias = "prefix", deserialize_with = "one_or_many")]
+    pub prefixes: Vec<String>,
+    /// 以提及 bot 开头的消息（`@bot help`）视为命令
+    pub mention_prefix: bool,
+    /// 默认语言，对应 resources/lang/ 下的消息目录
     pub language: String,
+    /// 命令异步任务的超时时间（秒）
+    pub command_timeout: u64,
+    /// 纯文本超过该字符数时改为发送 .txt 文件，0 表示关闭（仍会按平台上限拆成多条）
+    pub long_text_file_threshold: usize,
+    /// 分页结果的缓存时间（秒），过期后翻页按钮失效
+    pub page_ttl: u64,
+    /// 群聊中忽略未知命令（不回复错误），避免与其他 bot 的命令冲突
+    pub ignore_unknown_in_group: bool,
 
     pub telegram: Telegram,
     pub discord: Discord,
     pub onebot: Onebot,
 
+    pub reply: ReplyConfig,
+    pub rate_limit: RateLimitConfig,
+    /// 各平台的会话设置默认值，优先于上面的全局配置，低于 `/settings` 的会话设置
+    pub chat_defaults: ChatDefaults,
+    pub pipeline: PipelineConfig,
+
     pub commands: CommandsConfig,
 
+    pub storage: StorageConfig,
     pub proxy: ProxyConfig,
 }
 
+impl AppProperties {
+    pub fn owners_of(&self, platform: ChatPlatform) -> &[i64] {
+        match platform {
+            ChatPlatform::Telegram => &self.telegram.owners,
+            ChatPlatform::Discord => &self.discord.owners,
+            ChatPlatform::Onebot => &self.onebot.owners,
+        }
+    }
+}
+
 impl Default for AppProperties {
     fn default() -> Self {
         Self {
-            prefix: "/".to_string(),
+            prefixes: vec!["/".to_string()],
+            mention_prefix: true,
             language: "zh-cn".to_string(),
+            command_timeout: 30,
+            long_text_file_threshold: 0,
+            page_ttl: 600,
+            ignore_unknown_in_group: false,
             telegram: Telegram::default(),
             discord: Discord::default(),
             onebot: Onebot::default(),
+            reply: ReplyConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            chat_defaults: ChatDefaults::default(),
+            pipeline: PipelineConfig::default(),
             commands: CommandsConfig::default(),
+            storage: StorageConfig::default(),
             proxy: ProxyConfig::default(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct Telegram {
     pub enabled: bool,
     pub bot_token: String,
     pub bot_username: String,
+    /// 启动时通过 setMyCommands 同步命令菜单
+    pub sync_commands: bool,
+    /// 命令菜单的作用范围，每个范围各发布一次
+    pub command_scopes: Vec<TelegramCommandScope>,
+    /// bot 所有者的用户 id
+    pub owners: Vec<i64>,
+}
+
+impl Default for Telegram {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bot_token: String::new(),
+            bot_username: String::new(),
+            sync_commands: true,
+            command_scopes: vec![TelegramCommandScope::Default],
+            owners: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TelegramCommandScope {
+    Default,
+    Private,
+    Group,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -51,19 +120,243 @@ pub struct Telegram {
 pub struct Discord {
     pub enabled: bool,
     pub token: String,
+    /// 非空时斜杠命令只注册到这些服务器（立即生效），否则注册为全局命令
+    pub command_guilds: Vec<u64>,
+    /// bot 所有者的用户 id
+    pub owners: Vec<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct Onebot {
     pub enabled: bool,
+    pub mode: OnebotMode,
+    /// 正向 WS：OneBot 实现的 WS 地址
     pub ws_url: String,
+    /// 反向 WS / HTTP POST：bot 监听的本地地址
+    pub listen: String,
+    /// HTTP POST：OneBot 实现的 HTTP API 地址
+    pub api_url: String,
     pub access_token: String,
+    /// HTTP POST：上报签名密钥（X-Signature）
+    pub secret: String,
+    /// bot 所有者的 QQ 号
+    pub owners: Vec<i64>,
+}
+
+impl Default for Onebot {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: OnebotMode::ForwardWs,
+            ws_url: "ws://127.0.0.1:6700".to_string(),
+            listen: "127.0.0.1:6701".to_string(),
+            api_url: "http://127.0.0.1:5700".to_string(),
+            access_token: String::new(),
+            secret: String::new(),
+            owners: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnebotMode {
+    ForwardWs,
+    ReverseWs,
+    #[serde(alias = "http")]
+    HttpPost,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StorageBackend {
+    Sqlite,
+    /// 仅保存在内存中，重启后丢失
+    Memory,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct StorageConfig {
+    pub backend: StorageBackend,
+    /// SQLite 数据库文件，相对路径以工作目录为准（与 config/ 同级）
+    pub path: String,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backend: StorageBackend::Sqlite,
+            path: "data/lukosbot.db".to_string(),
+        }
+    }
+}
+
+/// 回复时是否引用触发消息
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReplyMode {
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ReplyConfig {
+    pub group: ReplyMode,
+    pub private: ReplyMode,
+    /// 按会话覆盖，键为 `<platform>:<group|private>:<chat_id>`，如 `telegram:group:-1001234567890`
+    pub chats: HashMap<String, ReplyMode>,
+}
+
+impl Default for ReplyConfig {
+    fn default() -> Self {
+        Self {
+            group: ReplyMode::Always,
+            private: ReplyMode::Never,
+            chats: HashMap::new(),
+        }
+    }
+}
+
+impl ReplyConfig {
+    /// `[reply.chats]` 中为该会话单独写的值
+    pub fn chat_mode(&self, addr: &Address) -> Option<ReplyMode> {
+        self.chats.get(&addr.key().config_name()).copied()
+    }
+
+    /// 按群 / 私聊区分的全局默认值
+    pub fn default_for(&self, addr: &Address) -> ReplyMode {
+        if addr.is_group {
+            self.group
+        } else {
+            self.private
+        }
+    }
+}
+
+/// 令牌桶限流；每条命令按其 `cost()`（默认 `default-cost`）扣除令牌
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// 每个用户的桶容量与每秒恢复的令牌数
+    pub user_capacity: f64,
+    pub user_refill: f64,
+    /// 每个会话的桶容量与每秒恢复的令牌数
+    pub chat_capacity: f64,
+    pub chat_refill: f64,
+    pub default_cost: u32,
+    /// 同一用户两次“请稍候”提示的最短间隔（秒）
+    pub notice_interval: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            user_capacity: 5.0,
+            user_refill: 0.5,
+            chat_capacity: 20.0,
+            chat_refill: 2.0,
+            default_cost: 1,
+            notice_interval: 10,
+        }
+    }
+}
+
+/// 会话设置的一层覆盖，`None` 表示沿用下一层。
+/// 既用于 `[chat-defaults.<platform>]`，也是 `/settings` 按会话持久化的内容。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ChatOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefixes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply: Option<ReplyMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled_commands: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_capacity: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_refill: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ChatDefaults {
+    pub telegram: ChatOverrides,
+    pub discord: ChatOverrides,
+    pub onebot: ChatOverrides,
+}
+
+impl ChatDefaults {
+    pub fn of(&self, platform: ChatPlatform) -> &ChatOverrides {
+        match platform {
+            ChatPlatform::Telegram => &self.telegram,
+            ChatPlatform::Discord => &self.discord,
+            ChatPlatform::Onebot => &self.onebot,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct PipelineConfig {
+    /// 按顺序执行的中间件：blacklist / rate-limit / logging / keyword-reply / censor
+    pub middlewares: Vec<String>,
+    pub blacklist: BlacklistConfig,
+    /// 关键词 -> 自动回复
+    pub keyword_reply: HashMap<String, String>,
+    pub censor: CensorConfig,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            middlewares: vec!["rate-limit".to_string()],
+            blacklist: BlacklistConfig::default(),
+            keyword_reply: HashMap::new(),
+            censor: CensorConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct BlacklistConfig {
+    /// `"<platform>:<user_id>"`
+    pub users: Vec<String>,
+    /// `"<platform>:<chat_id>"`
+    pub chats: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct CensorConfig {
+    pub words: Vec<String>,
+    pub mask: String,
+}
+
+impl Default for CensorConfig {
+    fn default() -> Self {
+        Self {
+            words: vec![],
+            mask: "*".to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct CommandsConfig {
+    /// 自定义别名：别名 -> 命令名
+    pub aliases: HashMap<String, String>,
     pub switch: SwitchConfig,
     pub github: GitHubConfig,
     pub music: MusicConfig,
@@ -72,18 +365,55 @@ pub struct CommandsConfig {
     pub control: ControlConfig,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-#[serde(default, rename_all = "kebab-case")]
-pub struct SwitchConfig {
-    pub weather: bool,
-    pub translate: bool,
+impl CommandsConfig {
+    /// `[commands.switch]` 中写成 `false` 的命令
+    pub fn switched_off(&self) -> impl Iterator<Item = String> + '_ {
+        off_names(&self.switch.0)
+    }
+
+    /// `[commands.control]` 中写成 `false`、不能在会话中开关的命令
+    pub fn locked(&self) -> impl Iterator<Item = String> + '_ {
+        off_names(&self.control.0)
+    }
+
+    pub fn is_locked(&self, name: &str) -> bool {
+        self.locked().any(|n| n == name)
+    }
+}
+
+fn off_names(m: &HashMap<String, bool>) -> impl Iterator<Item = String> + '_ {
+    m.iter()
+        .filter(|(_, on)| !**on)
+        .map(|(name, _)| name.trim().to_lowercase())
 }
 
+/// 命令名 -> 默认是否开启；关闭的命令在所有会话中默认关闭，会话管理员仍可 `/switch on`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct SwitchConfig(pub HashMap<String, bool>);
+
+/// 命令名 -> 是否允许会话自行开关；`false` 时各会话一律按 `[commands.switch]`
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct ControlConfig(pub HashMap<String, bool>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct GitHubConfig {
     pub enabled: bool,
     pub token: String,
+    /// 自动展开消息中的 GitHub 仓库 / Issue / PR 链接
+    pub unfurl: bool,
+}
+
+impl Default for GitHubConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            token: String::new(),
+            unfurl: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -120,13 +450,6 @@ pub struct TwentyFourConfig {
     pub time_limit: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-#[serde(default, rename_all = "kebab-case")]
-pub struct ControlConfig {
-    pub weather: bool,
-    pub translate: bool,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct ProxyConfig {
@@ -256,6 +579,20 @@ fn normalize_lf(s: &str) -> String {
     s.replace("\r\n", "\n").trim().to_string()
 }
 
+/// `prefixes` 既可写成单个字符串（旧的 `prefix = "/"`），也可写成列表
+fn one_or_many<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(d)? {
+        OneOrMany::One(s) => vec![s],
+        OneOrMany::Many(v) => v,
+    })
+}
+
 /// 读配置：
 /// 1) 文件不存在 -> 写模板
 /// 2) 仅基于“文件内容”反序列化一次（serde default 自动补全）-> pretty 序列化 -> 如有变化则写回（实现缺失项补全）
@@ -0,0 +1,425 @@
+// src/core/chat_settings.rs
+// 按会话的设置：会话覆盖 -> 平台默认（[chat-defaults.<platform>]）-> 全局配置
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, RwLock};
+
+use anyhow::Result;
+use tokio::sync::Mutex as AsyncMutex;
+
+use crate::config::{AppProperties, ChatOverrides, ReplyMode};
+use crate::core::prefix;
+use crate::core::rate_limiter::ChatLimit;
+use crate::i18n::{self, tr};
+use crate::model::{Address, ChatPlatform, MessageIn};
+use crate::storage::{Repository, Storage};
+
+const SETTINGS_NS: &str = "chat-settings";
+
+/// 可通过 `/settings` 修改的项，顺序即展示顺序
+pub const KEYS: &[&str] = &[
+    "language",
+    "prefixes",
+    "reply",
+    "disabled-commands",
+    "rate-limit",
+    "chat-capacity",
+    "chat-refill",
+];
+
+/// 三层合并后的结果
+#[derive(Debug, Clone)]
+pub struct ChatSettings {
+    pub language: String,
+    pub prefixes: Vec<String>,
+    pub reply: ReplyMode,
+    pub disabled_commands: BTreeSet<String>,
+    pub rate_limit: ChatLimit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Chat,
+    Platform,
+    Global,
+}
+
+impl Layer {
+    pub fn label(self, lang: &str) -> String {
+        match self {
+            Layer::Chat => tr!(lang, "layer.chat"),
+            Layer::Platform => tr!(lang, "layer.platform"),
+            Layer::Global => tr!(lang, "layer.global"),
+        }
+    }
+}
+
+impl ChatSettings {
+    /// 某一项的展示值
+    pub fn value_of(&self, key: &str) -> Option<String> {
+        Some(match key {
+            "language" => self.language.clone(),
+            "prefixes" => self.prefixes.join(" "),
+            "reply" => match self.reply {
+                ReplyMode::Always => "always".to_string(),
+                ReplyMode::Never => "never".to_string(),
+            },
+            "disabled-commands" => {
+                let v: Vec<&str> = self.disabled_commands.iter().map(String::as_str).collect();
+                v.join(" ")
+            }
+            "rate-limit" => on_off(self.rate_limit.enabled).to_string(),
+            "chat-capacity" => self.rate_limit.capacity.to_string(),
+            "chat-refill" => self.rate_limit.refill.to_string(),
+            _ => return None,
+        })
+    }
+}
+
+fn on_off(b: bool) -> &'static str {
+    if b { "on" } else { "off" }
+}
+
+fn is_set(o: &ChatOverrides, key: &str) -> bool {
+    match key {
+        "language" => o.language.is_some(),
+        "prefixes" => o.prefixes.is_some(),
+        "reply" => o.reply.is_some(),
+        "disabled-commands" => o.disabled_commands.is_some(),
+        "rate-limit" => o.rate_limit.is_some(),
+        "chat-capacity" => o.chat_capacity.is_some(),
+        "chat-refill" => o.chat_refill.is_some(),
+        _ => false,
+    }
+}
+
+/// 解析并写入一项；错误信息按 `lang` 直接回复给用户。
+/// `disabled-commands` 的命令名需由调用方先校验并换成正式名称。
+pub fn set_value(o: &mut ChatOverrides, key: &str, raw: &str, lang: &str) -> Result<(), String> {
+    let raw = raw.trim();
+    let number = |min: f64| -> Result<f64, String> {
+        match raw.parse::<f64>() {
+            Ok(n) if n.is_finite() && n >= min => Ok(n),
+            _ => Err(tr!(lang, "settings.invalid-number", key = key, min = min)),
+        }
+    };
+    match key {
+        // 只接受有消息目录的语言，`en`、`zh_CN` 等写法换成目录名
+        "language" => match i18n::supported(raw) {
+            Some(l) => o.language = Some(l.to_string()),
+            None => return Err(invalid_language(raw, lang)),
+        },
+        "prefixes" => o.prefixes = Some(prefix::parse_list(raw, lang)?),
+        "reply" => {
+            o.reply = Some(match raw.to_lowercase().as_str() {
+                "always" | "on" => ReplyMode::Always,
+                "never" | "off" => ReplyMode::Never,
+                _ => return Err(tr!(lang, "settings.invalid-reply")),
+            })
+        }
+        "disabled-commands" => {
+            let set: BTreeSet<String> = raw.split_whitespace().map(str::to_string).collect();
+            o.disabled_commands = Some(set.into_iter().collect());
+        }
+        "rate-limit" => {
+            o.rate_limit = Some(match raw.to_lowercase().as_str() {
+                "on" | "true" => true,
+                "off" | "false" => false,
+                _ => return Err(tr!(lang, "settings.invalid-rate-limit")),
+            })
+        }
+        "chat-capacity" => o.chat_capacity = Some(number(1.0)?),
+        "chat-refill" => o.chat_refill = Some(number(0.0)?),
+        _ => return Err(unknown_key(key, lang)),
+    }
+    Ok(())
+}
+
+pub fn unknown_key(key: &str, lang: &str) -> String {
+    tr!(lang, "settings.unknown-key", key = key, keys = KEYS.join(", "))
+}
+
+fn invalid_language(raw: &str, lang: &str) -> String {
+    let locales: Vec<&str> = i18n::locales().collect();
+    tr!(lang, "settings.invalid-language", value = raw, locales = locales.join(", "))
+}
+
+/// 清除一项；返回之前是否设置过
+pub fn clear_value(o: &mut ChatOverrides, key: &str) -> bool {
+    let was = is_set(o, key);
+    match key {
+        "language" => o.language = None,
+        "prefixes" => o.prefixes = None,
+        "reply" => o.reply = None,
+        "disabled-commands" => o.disabled_commands = None,
+        "rate-limit" => o.rate_limit = None,
+        "chat-capacity" => o.chat_capacity = None,
+        "chat-refill" => o.chat_refill = None,
+        _ => {}
+    }
+    was
+}
+
+/// 会话覆盖全部缓存在内存中，读取是同步的；修改时先写存储再更新缓存
+pub struct ChatSettingsStore {
+    props: Arc<AppProperties>,
+    repo: Repository<ChatOverrides>,
+    chats: RwLock<HashMap<String, ChatOverrides>>,
+    /// 串行化写入，避免同一会话的两次修改以相反顺序落盘
+    write: AsyncMutex<()>,
+}
+
+impl ChatSettingsStore {
+    pub fn new(props: Arc<AppProperties>, storage: &Storage) -> Self {
+        Self {
+            props,
+            repo: storage.repo(SETTINGS_NS),
+            chats: RwLock::new(HashMap::new()),
+            write: AsyncMutex::new(()),
+        }
+    }
+
+    /// 读入全部会话设置；需在迁移之后调用
+    pub async fn load(props: Arc<AppProperties>, storage: &Storage) -> Result<Self> {
+        let store = Self::new(props, storage);
+        let all: HashMap<String, ChatOverrides> = store.repo.all().await?.into_iter().collect();
+        *store.chats.write().unwrap() = all;
+        Ok(store)
+    }
+
+    pub fn get(&self, addr: &Address) -> ChatSettings {
+        self.merge(addr, self.overrides(addr))
+    }
+
+    /// 命令是否被 `[commands.control]` 锁定，不能在会话中开关
+    pub fn is_locked(&self, name: &str) -> bool {
+        self.props.commands.is_locked(name)
+    }
+
+    /// 以 `chat` 作为本会话的覆盖，与平台默认、全局配置合并
+    pub fn merge(&self, addr: &Address, chat: ChatOverrides) -> ChatSettings {
+        let platform = self.props.chat_defaults.of(addr.platform);
+        let global = &self.props;
+
+        let language = chat
+            .language
+            .or_else(|| platform.language.clone())
+            .unwrap_or_else(|| global.language.clone());
+        let prefixes = chat
+            .prefixes
+            .or_else(|| platform.prefixes.clone())
+            .unwrap_or_else(|| global.prefixes.clone())
+            .into_iter()
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect();
+        // 单独列出的会话比整个平台的默认值更具体
+        let reply = chat
+            .reply
+            .or_else(|| global.reply.chat_mode(addr))
+            .or(platform.reply)
+            .unwrap_or_else(|| global.reply.default_for(addr));
+        let mut disabled_commands: BTreeSet<String> = chat
+            .disabled_commands
+            .or_else(|| platform.disabled_commands.clone())
+            .unwrap_or_else(|| global.commands.switched_off().collect())
+            .into_iter()
+            .collect();
+        // `[commands.control]` 锁定的命令不受会话和平台设置影响
+        for name in global.commands.locked() {
+            if global.commands.switched_off().any(|n| n == name) {
+                disabled_commands.insert(name);
+            } else {
+                disabled_commands.remove(&name);
+            }
+        }
+        // 会话只能收紧会话桶，不能放宽到超过平台默认 / 全局配置
+        let capacity = platform.chat_capacity.unwrap_or(global.rate_limit.chat_capacity);
+        let refill = platform.chat_refill.unwrap_or(global.rate_limit.chat_refill);
+        let rate_limit = ChatLimit {
+            enabled: chat
+                .rate_limit
+                .or(platform.rate_limit)
+                .unwrap_or(global.rate_limit.enabled),
+            capacity: chat.chat_capacity.map_or(capacity, |c| c.min(capacity)),
+            refill: chat.chat_refill.map_or(refill, |r| r.min(refill)),
+        };
+
+        ChatSettings {
+            language,
+            prefixes,
+            reply,
+            disabled_commands,
+            rate_limit,
+        }
+    }
+
+    /// 回复所用的语言：本会话显式设置的语言优先，其次是发送者客户端的语言
+    /// （如 Telegram 的 `language_code`、Discord 的 locale），最后是平台默认 / 全局配置
+    pub fn language_for(&self, input: &MessageIn) -> &'static str {
+        let chat = self.overrides(&input.addr).language;
+        if let Some(l) = chat.as_deref().and_then(i18n::supported) {
+            return l;
+        }
+        input
+            .sender
+            .language
+            .as_deref()
+            .and_then(i18n::supported)
+            .unwrap_or_else(|| i18n::resolve(&self.get(&input.addr).language))
+    }
+
+    /// 平台的默认语言，用于发布命令菜单等不针对具体会话的文本
+    pub fn platform_language(&self, platform: ChatPlatform) -> &'static str {
+        let platform = self.props.chat_defaults.of(platform);
+        i18n::resolve(platform.language.as_deref().unwrap_or(&self.props.language))
+    }
+
+    /// 本会话自己的覆盖（不含平台默认）
+    pub fn overrides(&self, addr: &Address) -> ChatOverrides {
+        self.chats
+            .read()
+            .unwrap()
+            .get(&key_of(addr))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// 某一项的值来自哪一层
+    pub fn layer_of(&self, addr: &Address, key: &str) -> Layer {
+        if is_set(&self.overrides(addr), key) {
+            Layer::Chat
+        } else if is_set(self.props.chat_defaults.of(addr.platform), key) {
+            Layer::Platform
+        } else {
+            Layer::Global
+        }
+    }
+
+    /// 修改本会话的覆盖并持久化；全部清空时删除记录
+    pub async fn update<R>(
+        &self,
+        addr: &Address,
+        f: impl FnOnce(&mut ChatOverrides) -> R,
+    ) -> Result<R> {
+        let _g = self.write.lock().await;
+        let key = key_of(addr);
+        let mut o = self.overrides(addr);
+        let r = f(&mut o);
+        self.save(&key, o).await?;
+        Ok(r)
+    }
+
+    async fn save(&self, key: &str, o: ChatOverrides) -> Result<()> {
+        if o == ChatOverrides::default() {
+            self.repo.delete(key).await?;
+            self.chats.write().unwrap().remove(key);
+        } else {
+            self.repo.put(key, &o).await?;
+            self.chats.write().unwrap().insert(key.to_string(), o);
+        }
+        Ok(())
+    }
+
+}
+
+/// `onebot:group:123`；同号的群与私聊是两条记录
+fn key_of(addr: &Address) -> String {
+    addr.key().to_string().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reply_precedence() {
+        let mut props = AppProperties::default();
+        props.reply.group = ReplyMode::Always;
+        props.reply.private = ReplyMode::Always;
+        props.reply.chats.insert("onebot:group:1".to_string(), ReplyMode::Always);
+        props.chat_defaults.onebot.reply = Some(ReplyMode::Never);
+        let storage = Storage::in_memory();
+        storage.migrate().await.unwrap();
+        let store = ChatSettingsStore::new(Arc::new(props), &storage);
+
+        let listed = Address::new(ChatPlatform::Onebot, 1i64, true);
+        let other = Address::new(ChatPlatform::Onebot, 2i64, true);
+        let telegram = Address::new(ChatPlatform::Telegram, 2i64, true);
+        // [reply.chats] > 平台默认 > 全局群 / 私聊默认
+        assert_eq!(store.get(&listed).reply, ReplyMode::Always);
+        assert_eq!(store.get(&other).reply, ReplyMode::Never);
+        assert_eq!(store.get(&telegram).reply, ReplyMode::Always);
+
+        // 会话自己的设置最优先
+        store.update(&listed, |o| o.reply = Some(ReplyMode::Never)).await.unwrap();
+        assert_eq!(store.get(&listed).reply, ReplyMode::Never);
+    }
+
+    /// `[commands.switch]` 只是默认值；`[commands.control]` 锁定的命令不受会话设置影响
+    #[tokio::test]
+    async fn config_switches_and_locks() {
+        let mut props = AppProperties::default();
+        props.commands.switch.0.insert("GitHub".to_string(), false);
+        props.commands.switch.0.insert("help".to_string(), true);
+        props.commands.switch.0.insert("ping".to_string(), false);
+        props.commands.control.0.insert("ping".to_string(), false);
+        props.commands.control.0.insert("help".to_string(), false);
+        let storage = Storage::in_memory();
+        storage.migrate().await.unwrap();
+        let store = ChatSettingsStore::new(Arc::new(props), &storage);
+        let addr = Address::new(ChatPlatform::Telegram, 1i64, true);
+
+        let disabled = store.get(&addr).disabled_commands;
+        assert_eq!(disabled.into_iter().collect::<Vec<_>>(), ["github", "ping"]);
+        assert!(store.is_locked("ping") && !store.is_locked("github"));
+
+        store
+            .update(&addr, |o| o.disabled_commands = Some(vec!["help".to_string()]))
+            .await
+            .unwrap();
+        let disabled = store.get(&addr).disabled_commands;
+        assert_eq!(disabled.into_iter().collect::<Vec<_>>(), ["ping"]);
+    }
+
+    #[tokio::test]
+    async fn chat_bucket_can_only_be_tightened() {
+        let storage = Storage::in_memory();
+        storage.migrate().await.unwrap();
+        let store = ChatSettingsStore::new(Arc::new(AppProperties::default()), &storage);
+        let addr = Address::new(ChatPlatform::Telegram, 1i64, true);
+        let global = AppProperties::default().rate_limit;
+
+        store
+            .update(&addr, |o| {
+                o.chat_capacity = Some(global.chat_capacity * 10.0);
+                o.chat_refill = Some(global.chat_refill / 2.0);
+            })
+            .await
+            .unwrap();
+        let limit = store.get(&addr).rate_limit;
+        assert_eq!(limit.capacity, global.chat_capacity);
+        assert_eq!(limit.refill, global.chat_refill / 2.0);
+    }
+
+    #[tokio::test]
+    async fn private_and_group_with_same_id_are_separate() {
+        let storage = Storage::in_memory();
+        storage.migrate().await.unwrap();
+        let store = ChatSettingsStore::new(Arc::new(AppProperties::default()), &storage);
+        let dm = Address::new(ChatPlatform::Onebot, 123i64, false);
+        let group = Address::new(ChatPlatform::Onebot, 123i64, true);
+
+        store
+            .update(&dm, |o| o.disabled_commands = Some(vec!["github".to_string()]))
+            .await
+            .unwrap();
+        assert!(store.get(&dm).disabled_commands.contains("github"));
+        assert!(store.get(&group).disabled_commands.is_empty());
+        assert_eq!(repo_keys(&storage).await, vec!["onebot:private:123"]);
+    }
+
+    async fn repo_keys(storage: &Storage) -> Vec<String> {
+        let repo: Repository<ChatOverrides> = storage.repo(SETTINGS_NS);
+        repo.all().await.unwrap().into_iter().map(|(k, _)| k).collect()
+    }
+}
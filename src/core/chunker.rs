@@ -0,0 +1,364 @@
+// src/core/chunker.rs
+// 按平台长度上限拆分出站消息；以片段为单位拆分，保证不会切断格式或代码块
+use std::collections::VecDeque;
+
+use crate::model::{Attachment, MessageOut, Segment};
+
+/// 拆分的最小单位：一行文本，或代码块中的一行（`block` 区分相邻的不同代码块）
+#[derive(Clone)]
+enum Piece {
+    Seg(Segment),
+    Code {
+        block: usize,
+        lang: Option<String>,
+        line: String,
+    },
+}
+
+/// 把 `out` 拆成若干条消息，使每条经 `measure`（渲染后按平台口径计算长度）不超过 `limit`。
+/// 优先在空行（段落）处断开，其次在行尾；单行仍超长时才在行内对半切。
+/// 引用回复只保留在第一条上，按钮只保留在最后一条上。
+pub fn split(
+    out: MessageOut,
+    limit: usize,
+    measure: impl Fn(&[Segment]) -> usize,
+) -> Vec<MessageOut> {
+    if measure(&out.segments) <= limit {
+        return vec![out];
+    }
+
+    // 逐片累加渲染长度，不必每加一片都重新渲染整条（否则是平方级）。
+    // 同一代码块的后续行只计正文，引用块逐行渲染再补一个换行，估计值只会偏大
+    let cost = |prev: Option<&Piece>, p: &Piece| match (prev, p) {
+        (Some(Piece::Code { block: b, .. }), Piece::Code { block, lang, line }) if b == block => {
+            let with = |code: &str| Segment::CodeBlock {
+                lang: lang.clone(),
+                code: code.to_string(),
+            };
+            measure(&[with(line)]).saturating_sub(measure(&[with("")]))
+        }
+        (Some(Piece::Seg(Segment::Quote(_))), Piece::Seg(Segment::Quote(_))) => {
+            measure(&assemble(std::slice::from_ref(p))) + 1
+        }
+        _ => measure(&assemble(std::slice::from_ref(p))),
+    };
+
+    let mut queue: VecDeque<Piece> = pieces_of(&out.segments).into();
+    let mut chunks: Vec<Vec<Segment>> = vec![];
+    let mut cur: Vec<Piece> = vec![];
+    let mut len = 0;
+
+    while let Some(p) = queue.pop_front() {
+        let add = cost(cur.last(), &p);
+        if len + add <= limit {
+            len += add;
+            cur.push(p);
+            continue;
+        }
+
+        if cur.is_empty() {
+            match halve(&p) {
+                Some((a, b)) => {
+                    queue.push_front(b);
+                    queue.push_front(a);
+                }
+                None => chunks.push(assemble(&[p])),
+            }
+            continue;
+        }
+
+        // 回退到后半段里最近的段落边界，避免把一段话拆到两条消息
+        let cut = cur
+            .iter()
+            .rposition(is_paragraph_break)
+            .map(|i| i + 1)
+            .filter(|i| *i >= cur.len() / 2)
+            .unwrap_or(cur.len());
+        let rest = cur.split_off(cut);
+        chunks.push(assemble(&cur));
+        cur.clear();
+        len = 0;
+
+        queue.push_front(p);
+        for r in rest.into_iter().rev() {
+            queue.push_front(r);
+        }
+    }
+    if !cur.is_empty() {
+        chunks.push(assemble(&cur));
+    }
+
+    let mut reply_to = out.reply_to;
+    let mut parts: Vec<MessageOut> = chunks
+        .into_iter()
+        .filter(|segs| !is_blank(segs))
+        .map(|segs| {
+            let mut m = MessageOut::new(out.addr.clone(), segs);
+            m.reply_to = reply_to.take();
+            m
+        })
+        .collect();
+    if let Some(last) = parts.last_mut() {
+        last.keyboard = out.keyboard;
+    }
+    parts
+}
+
+/// 纯文本超过 `threshold` 个字符时，把文字部分整体改为一个 .txt 附件；`threshold` 为 0 时不处理
+pub fn text_as_file(out: MessageOut, threshold: usize) -> MessageOut {
+    if threshold == 0 {
+        return out;
+    }
+    let text = out.plain_text();
+    if text.chars().count() <= threshold {
+        return out;
+    }
+
+    let mut segs: Vec<Segment> = vec![Segment::Attachment(Attachment::file_bytes(
+        "output.txt",
+        text.into_bytes(),
+        "text/plain",
+    ))];
+    segs.extend(
+        out.segments
+            .iter()
+            .filter(|s| matches!(s, Segment::Attachment(_)))
+            .cloned(),
+    );
+
+    MessageOut { segments: segs, ..out }
+}
+
+/// Telegram 按 UTF-16 码元计算长度
+pub fn utf16_len(s: &str) -> usize {
+    s.encode_utf16().count()
+}
+
+fn pieces_of(segs: &[Segment]) -> Vec<Piece> {
+    let mut out = vec![];
+    for (i, s) in segs.iter().enumerate() {
+        match s {
+            Segment::Text(t) => out.extend(
+                t.split_inclusive('\n')
+                    .map(|l| Piece::Seg(Segment::Text(l.to_string()))),
+            ),
+            Segment::Quote(t) => {
+                out.extend(t.lines().map(|l| Piece::Seg(Segment::Quote(l.to_string()))))
+            }
+            Segment::CodeBlock { lang, code } => {
+                out.extend(code.split_inclusive('\n').map(|l| Piece::Code {
+                    block: i,
+                    lang: lang.clone(),
+                    line: l.to_string(),
+                }))
+            }
+            other => out.push(Piece::Seg(other.clone())),
+        }
+    }
+    out
+}
+
+/// 把片段拼回 `Segment`：相邻文本合并，同一代码块的行合并回一个代码块
+fn assemble(pieces: &[Piece]) -> Vec<Segment> {
+    let mut out: Vec<Segment> = vec![];
+    let mut code_block: Option<usize> = None;
+
+    for p in pieces {
+        match p {
+            Piece::Code { block, lang, line } => {
+                if code_block == Some(*block)
+                    && let Some(Segment::CodeBlock { code, .. }) = out.last_mut()
+                {
+                    code.push_str(line);
+                    continue;
+                }
+                code_block = Some(*block);
+                out.push(Segment::CodeBlock {
+                    lang: lang.clone(),
+                    code: line.clone(),
+                });
+            }
+            Piece::Seg(s) => {
+                code_block = None;
+                match (out.last_mut(), s) {
+                    (Some(Segment::Text(prev)), Segment::Text(t)) => prev.push_str(t),
+                    (Some(Segment::Quote(prev)), Segment::Quote(t)) => {
+                        prev.push('\n');
+                        prev.push_str(t);
+                    }
+                    _ => out.push(s.clone()),
+                }
+            }
+        }
+    }
+
+    for s in &mut out {
+        if let Segment::CodeBlock { code, .. } = s {
+            let trimmed = code.trim_end_matches('\n').len();
+            code.truncate(trimmed);
+        }
+    }
+    out
+}
+
+/// 单个片段本身超长时对半切开；附件、提及等无法再切
+fn halve(p: &Piece) -> Option<(Piece, Piece)> {
+    let split_str = |s: &str| -> Option<(String, String)> {
+        let n = s.chars().count();
+        if n < 2 {
+            return None;
+        }
+        let at = s.char_indices().nth(n / 2).map(|(i, _)| i)?;
+        Some((s[..at].to_string(), s[at..].to_string()))
+    };
+
+    match p {
+        Piece::Code { block, lang, line } => {
+            let (a, b) = split_str(line)?;
+            let mk = |line| Piece::Code {
+                block: *block,
+                lang: lang.clone(),
+                line,
+            };
+            // 前半段补上换行，两半各自成行
+            Some((mk(a + "\n"), mk(b)))
+        }
+        Piece::Seg(s) => {
+            let (wrap, t): (fn(String) -> Segment, &String) = match s {
+                Segment::Text(t) => (Segment::Text, t),
+                Segment::Bold(t) => (Segment::Bold, t),
+                Segment::Italic(t) => (Segment::Italic, t),
+                Segment::Code(t) => (Segment::Code, t),
+                Segment::Quote(t) => (Segment::Quote, t),
+                _ => return None,
+            };
+            let (a, b) = split_str(t)?;
+            Some((Piece::Seg(wrap(a)), Piece::Seg(wrap(b))))
+        }
+    }
+}
+
+fn is_paragraph_break(p: &Piece) -> bool {
+    match p {
+        Piece::Seg(Segment::Text(t)) => t.ends_with('\n') && t.trim().is_empty(),
+        Piece::Seg(Segment::LineBreak) => true,
+        _ => false,
+    }
+}
+
+fn is_blank(segs: &[Segment]) -> bool {
+    segs.iter().all(|s| match s {
+        Segment::Text(t) => t.trim().is_empty(),
+        Segment::LineBreak => true,
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Address, Button, ChatPlatform};
+
+    fn addr() -> Address {
+        Address::new(ChatPlatform::Onebot, 1i64, true)
+    }
+
+    /// 近似 Discord markdown 的渲染长度（按字符计）
+    fn render_len(segs: &[Segment]) -> usize {
+        segs.iter()
+            .map(|s| match s {
+                Segment::Bold(t) => t.chars().count() + 4,
+                Segment::CodeBlock { lang, code } => {
+                    code.chars().count() + lang.as_deref().unwrap_or("").len() + 8
+                }
+                Segment::Quote(t) => t.lines().map(|l| l.chars().count() + 3).sum(),
+                other => crate::model::plain_text(std::slice::from_ref(other)).chars().count(),
+            })
+            .sum()
+    }
+
+    fn code_of(m: &MessageOut) -> Vec<&str> {
+        m.segments
+            .iter()
+            .filter_map(|s| match s {
+                Segment::CodeBlock { lang, code } => {
+                    assert_eq!(lang.as_deref(), Some("rust"));
+                    Some(code.as_str())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn short_message_is_untouched() {
+        let parts = split(MessageOut::text(addr(), "hello"), 10, render_len);
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].plain_text(), "hello");
+    }
+
+    #[test]
+    fn long_line_splits_on_char_boundaries() {
+        let text = "汉字🦀".repeat(10);
+        let parts = split(MessageOut::text(addr(), text.clone()), 7, render_len);
+        assert!(parts.len() > 1);
+        assert!(parts.iter().all(|m| render_len(&m.segments) <= 7));
+        let joined: String = parts.iter().map(MessageOut::plain_text).collect();
+        assert_eq!(joined, text);
+    }
+
+    #[test]
+    fn code_blocks_are_reopened_in_every_part() {
+        let lines: Vec<String> = (0..10).map(|i| format!("let x{i} = {i};\n")).collect();
+        let out = MessageOut::new(
+            addr(),
+            vec![
+                Segment::text("intro\n"),
+                Segment::CodeBlock {
+                    lang: Some("rust".into()),
+                    code: lines.concat(),
+                },
+            ],
+        );
+        let parts = split(out, 60, render_len);
+        assert!(parts.len() > 1);
+        assert!(parts.iter().all(|m| render_len(&m.segments) <= 60));
+
+        let code: Vec<&str> = parts.iter().flat_map(code_of).collect();
+        assert!(parts[1..].iter().all(|m| code_of(m).len() == 1));
+        assert_eq!(code.join("\n") + "\n", lines.concat());
+    }
+
+    #[test]
+    fn prefers_paragraph_breaks() {
+        let text = "first paragraph\nstill first\n\nsecond paragraph\n";
+        let parts = split(MessageOut::text(addr(), text), 40, render_len);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].plain_text(), "first paragraph\nstill first\n\n");
+        assert_eq!(parts[1].plain_text(), "second paragraph\n");
+    }
+
+    #[test]
+    fn reply_goes_first_and_keyboard_last() {
+        let mut out = MessageOut::text(addr(), "line one\n\nline two\n\nline three\n");
+        out.reply_to = Some(7i64.into());
+        out.keyboard = vec![vec![Button::new("next", "page:2")]];
+        let parts = split(out, 12, render_len);
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].reply_to.is_some());
+        assert!(parts[1..].iter().all(|m| m.reply_to.is_none()));
+        assert!(parts[..2].iter().all(|m| m.keyboard.is_empty()));
+        assert_eq!(parts[2].keyboard.len(), 1);
+    }
+
+    #[test]
+    fn long_text_becomes_file() {
+        let out = text_as_file(MessageOut::text(addr(), "x".repeat(20)), 10);
+        assert!(matches!(
+            out.segments.as_slice(),
+            [Segment::Attachment(a)] if a.name.as_deref() == Some("output.txt")
+        ));
+        let out = text_as_file(MessageOut::text(addr(), "x".repeat(20)), 0);
+        assert_eq!(out.plain_text().len(), 20);
+    }
+}
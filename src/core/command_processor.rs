@@ -2,45 +2,185 @@ use crate::config::AppProperties;
 use crate::core::command_registry::CommandRegistry;
 use crate::core::command_source::CommandSource;
 use crate::core::dispatcher::CommandDispatcher;
+use crate::core::message_sender_hub::MessageSenderHub;
+use crate::core::permission::{Permission, PermissionResolver};
+use crate::core::syntax_error;
+use crate::i18n::tr;
 use crate::model::{MessageIn, MessageOut};
 use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
 
 pub struct CommandProcessor {
     props: Arc<AppProperties>,
     dispatcher: CommandDispatcher<CommandSource>,
     registry: Arc<CommandRegistry>,
+    permissions: PermissionResolver,
 }
 
 impl CommandProcessor {
-    pub fn new(props: Arc<AppProperties>, registry: Arc<CommandRegistry>) -> Self {
+    pub fn new(
+        props: Arc<AppProperties>,
+        registry: Arc<CommandRegistry>,
+        hub: MessageSenderHub,
+    ) -> Self {
         let mut dispatcher = CommandDispatcher::<CommandSource>::new();
-        for c in registry.all() {
-            c.register(&mut dispatcher);
-        }
+        registry.register_all(&mut dispatcher);
 
         Self {
+            permissions: PermissionResolver::new(props.clone(), hub),
             props,
             dispatcher,
             registry,
         }
     }
 
-    pub fn handle(&self, input: MessageIn) -> Vec<MessageOut> {
-        let cmd_line: String = {
-            let t = input.text.trim();
-            let rest = match t.strip_prefix(&self.props.prefix) {
-                Some(r) => r,
-                None => return vec![],
-            };
-            rest.trim().to_string()
+    pub async fn handle(&self, input: MessageIn) -> Vec<MessageOut> {
+        let Some((prefix, cmd_line)) = self
+            .registry
+            .prefixes()
+            .parse(&input)
+            .map(|(p, l)| (p, l.to_string()))
+        else {
+            return vec![];
+        };
+
+        let timeout = self.timeout_for(&cmd_line);
+        let target = cmd_line
+            .split_whitespace()
+            .next()
+            .and_then(|name| self.registry.find(name))
+            .cloned();
+        // 只在命中已注册命令时解析权限，避免为无关消息向平台查询管理员
+        let permission = match target {
+            Some(_) => self.permissions.level_of(&input).await,
+            None => Permission::Everyone,
         };
+        let lang = self.registry.settings().language_for(&input);
+        let src = CommandSource::new(input)
+            .with_permission(permission)
+            .with_lang(lang);
 
-        let src = CommandSource::new(input);
+        if let Some(c) = target
+            .as_ref()
+            .filter(|c| !self.registry.is_enabled(&src.in_msg().addr, c.as_ref()))
+        {
+            src.reply(tr!(lang, "processor.disabled", command = format!("{prefix}{}", c.name())));
+        } else if let Some(c) = target.as_ref().filter(|c| c.permission() > permission) {
+            src.reply(tr!(
+                lang,
+                "processor.denied",
+                command = format!("{prefix}{}", c.name()),
+                role = c.permission().label(lang),
+            ));
+        } else if let Err(e) = self.dispatcher.execute(cmd_line.as_str(), src.clone()) {
+            // 群里别的 bot 的命令不予理会
+            if self.props.ignore_unknown_in_group
+                && src.in_msg().addr.is_group
+                && syntax_error::is_unknown_command(&self.dispatcher, &cmd_line)
+            {
+                return vec![];
+            }
+            src.reply_segments(syntax_error::report(
+                &self.dispatcher,
+                &src,
+                &cmd_line,
+                &e,
+                &prefix,
+            ));
+        }
 
-        if let Err(e) = self.dispatcher.execute(cmd_line.as_str(), src.clone()) {
-            src.reply(format!("命令错误: {}", e.message()));
+        if tokio::time::timeout(timeout, src.join_tasks()).await.is_err() {
+            warn!("command timed out after {:?}: {}", timeout, cmd_line);
+            src.reply(tr!(lang, "processor.timeout"));
         }
 
         src.take_outs()
     }
+
+    /// 命令自身声明的超时优先，否则使用全局 `command-timeout`。
+    fn timeout_for(&self, cmd_line: &str) -> Duration {
+        let name = cmd_line.split_whitespace().next().unwrap_or("");
+        self.registry
+            .find(name)
+            .and_then(|c| c.timeout())
+            .unwrap_or(Duration::from_secs(self.props.command_timeout))
+    }
+}
+
+/// 测试共用的命令环境：内存存储、会话设置、命令表、发送中心与命令处理器
+#[cfg(test)]
+pub(crate) struct TestBot {
+    pub props: Arc<AppProperties>,
+    pub settings: Arc<crate::core::chat_settings::ChatSettingsStore>,
+    pub registry: Arc<CommandRegistry>,
+    pub hub: MessageSenderHub,
+    pub processor: Arc<CommandProcessor>,
+}
+
+#[cfg(test)]
+impl TestBot {
+    pub async fn new(props: AppProperties) -> Self {
+        use crate::core::chat_settings::ChatSettingsStore;
+        use crate::core::paginator::Paginator;
+        use crate::storage::Storage;
+
+        let props = Arc::new(props);
+        let storage = Storage::in_memory();
+        storage.migrate().await.unwrap();
+        let settings = Arc::new(ChatSettingsStore::new(props.clone(), &storage));
+        let registry = CommandRegistry::build(props.clone(), settings.clone()).unwrap();
+        let hub = MessageSenderHub::new(0, Arc::new(Paginator::new(Duration::from_secs(60))));
+        let processor = Arc::new(CommandProcessor::new(
+            props.clone(),
+            registry.clone(),
+            hub.clone(),
+        ));
+        Self {
+            props,
+            settings,
+            registry,
+            hub,
+            processor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Address, ChatPlatform};
+
+    async fn reply(bot: &TestBot, line: &str, admin: bool) -> String {
+        let group = Address::new(ChatPlatform::Onebot, 1i64, true);
+        let mut input = MessageIn::new(group, 2i64, line.to_string());
+        input.sender.is_chat_admin = Some(admin);
+        let outs = bot.processor.handle(input).await;
+        outs.iter().map(|o| o.plain_text()).collect()
+    }
+
+    /// 帮助列表和语法错误的候选项都只列出当前用户有权执行的命令
+    #[tokio::test]
+    async fn help_and_syntax_errors_hide_commands_above_permission() {
+        let bot = TestBot::new(AppProperties::default()).await;
+
+        let help = reply(&bot, "/help", false).await;
+        assert!(help.contains("/ping"), "{help}");
+        assert!(!help.contains("/switch"), "{help}");
+        assert!(!help.contains("/settings"), "{help}");
+        let help = reply(&bot, "/help", true).await;
+        assert!(help.contains("/switch"), "{help}");
+
+        let unknown = reply(&bot, "/nope", false).await;
+        assert!(unknown.contains("ping"), "{unknown}");
+        assert!(!unknown.contains("switch"), "{unknown}");
+        assert!(!unknown.contains("settings"), "{unknown}");
+
+        // 无权执行的命令按不存在处理
+        let missing = reply(&bot, "/help nope", false)
+            .await
+            .replace("nope", "switch");
+        assert_eq!(reply(&bot, "/help switch", false).await, missing);
+        assert_ne!(reply(&bot, "/help switch", true).await, missing);
+    }
 }
@@ -1,47 +1,197 @@
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
-use crate::commands::{github::GitHubCommand, help::HelpCommand, ping::PingCommand};
+use anyhow::{bail, Result};
+use tracing::warn;
+
+use crate::commands::{
+    github::{GitHubApi, GitHubCommand},
+    help::HelpCommand,
+    ping::PingCommand,
+    prefix::PrefixCommand,
+    settings::SettingsCommand,
+    switch::SwitchCommand,
+};
 use crate::config::AppProperties;
+use crate::core::chat_settings::ChatSettingsStore;
 use crate::core::command_source::CommandSource;
-use crate::core::dispatcher::CommandDispatcher;
+use crate::core::dispatcher::{literal, CommandDispatcher};
+use crate::core::message_handler::MessageHandler;
+use crate::core::permission::Permission;
+use crate::core::prefix::Prefixes;
+use crate::handlers::github_link::GitHubLinkHandler;
+use crate::model::Address;
 
 pub trait BotCommand: Send + Sync {
     fn name(&self) -> &'static str;
-    fn description(&self) -> &'static str;
-    fn usage(&self) -> &'static str;
+    /// 一行说明，按 `lang` 从消息目录取得
+    fn description(&self, lang: &str) -> String;
+    fn usage(&self, lang: &str) -> String;
+
+    /// 内置别名，注册为指向本命令的 brigadier 重定向
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
 
     fn visible(&self) -> bool {
         true
     }
 
+    /// 执行所需的最低权限；低于该权限的用户在帮助中看不到此命令。
+    /// 只写入根节点的 `requires`，子命令要求更高权限时需在 `register` 中自行加上 `requires`。
+    fn permission(&self) -> Permission {
+        Permission::Everyone
+    }
+
+    /// 能否通过 `/switch` 在会话中关闭；`help`、`switch` 等基础命令返回 `false`
+    fn switchable(&self) -> bool {
+        true
+    }
+
+    /// 每次执行消耗的限流令牌；`None` 表示使用全局 `default-cost`
+    fn cost(&self) -> Option<u32> {
+        None
+    }
+
+    /// 同一用户两次执行之间的最短间隔
+    fn cooldown(&self) -> Option<Duration> {
+        None
+    }
+
+    /// 异步任务的最长等待时间；`None` 表示使用全局 `command-timeout`。
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+
     fn register(&self, d: &mut CommandDispatcher<CommandSource>);
 }
 
 pub struct CommandRegistry {
     cmds: Vec<Arc<dyn BotCommand>>,
+    /// 别名 -> 命令名，含内置别名与 `[commands.aliases]`
+    aliases: HashMap<String, &'static str>,
+    /// 普通消息处理器，按优先级从高到低排列
+    handlers: Vec<Arc<dyn MessageHandler>>,
+    settings: Arc<ChatSettingsStore>,
+    prefixes: Arc<Prefixes>,
 }
 
 impl CommandRegistry {
-    pub fn new() -> Self {
-        Self { cmds: vec![] }
-    }
+    pub fn build(
+        props: Arc<AppProperties>,
+        settings: Arc<ChatSettingsStore>,
+    ) -> Result<Arc<Self>> {
+        let mut err = None;
+        let reg = Arc::new_cyclic(|weak_reg| {
+            let help = HelpCommand::new(weak_reg.clone());
+            let switch = SwitchCommand::new(weak_reg.clone());
+            let prefix = PrefixCommand::new(weak_reg.clone());
+            let settings_cmd = SettingsCommand::new(weak_reg.clone());
 
-    pub fn build(props: Arc<AppProperties>) -> Arc<Self> {
-        Arc::new_cyclic(|weak_reg| {
-            let help = HelpCommand::new(weak_reg.clone(), props.clone());
-            let github = GitHubCommand::new(
-                Option::from(props.commands.github.token.clone()),
-                &props.proxy.clone(),
-            );
+            let mut cmds = vec![Arc::new(PingCommand) as Arc<dyn BotCommand>];
+            let mut handlers: Vec<Arc<dyn MessageHandler>> = vec![];
+            // 配置中关闭的命令不注册
+            if props.commands.github.enabled {
+                let api = Arc::new(GitHubApi::new(
+                    Option::from(props.commands.github.token.clone()),
+                    &props.proxy.clone(),
+                ));
+                cmds.push(Arc::new(GitHubCommand::new(api.clone())));
+                if props.commands.github.unfurl {
+                    handlers.push(Arc::new(GitHubLinkHandler::new(api)));
+                }
+            }
+            cmds.push(Arc::new(switch));
+            cmds.push(Arc::new(prefix));
+            cmds.push(Arc::new(settings_cmd));
+            cmds.push(Arc::new(help));
+            handlers.sort_by_key(|h| std::cmp::Reverse(h.priority()));
 
+            let aliases = resolve_aliases(&cmds, &props.commands.aliases).unwrap_or_else(|e| {
+                err = Some(e);
+                HashMap::new()
+            });
             CommandRegistry {
-                cmds: vec![
-                    Arc::new(PingCommand) as Arc<dyn BotCommand>,
-                    Arc::new(github) as Arc<dyn BotCommand>,
-                    Arc::new(help) as Arc<dyn BotCommand>,
-                ],
+                cmds,
+                aliases,
+                handlers,
+                prefixes: Arc::new(Prefixes::new(&props, settings.clone())),
+                settings,
+            }
+        });
+
+        let switches = props.commands.switched_off().chain(props.commands.locked());
+        for name in switches.filter(|n| reg.find(n).is_none()) {
+            warn!("unknown command `{name}` in [commands.switch] / [commands.control], ignored");
+        }
+
+        match err {
+            Some(e) => Err(e),
+            None => Ok(reg),
+        }
+    }
+
+    /// 按命令名或别名查找，忽略大小写
+    pub fn find(&self, name: &str) -> Option<&Arc<dyn BotCommand>> {
+        let name = name.to_lowercase();
+        let name = self.aliases.get(&name).copied().unwrap_or(&name);
+        self.cmds.iter().find(|c| c.name() == name)
+    }
+
+    pub fn handlers(&self) -> &[Arc<dyn MessageHandler>] {
+        &self.handlers
+    }
+
+    /// 该命令在此会话中是否可用（未被 `/switch off` 或 `disabled-commands` 关闭）
+    pub fn is_enabled(&self, addr: &Address, cmd: &dyn BotCommand) -> bool {
+        !cmd.switchable() || !self.settings.get(addr).disabled_commands.contains(cmd.name())
+    }
+
+    pub fn settings(&self) -> &Arc<ChatSettingsStore> {
+        &self.settings
+    }
+
+    pub fn prefixes(&self) -> &Arc<Prefixes> {
+        &self.prefixes
+    }
+
+    /// 某个命令的全部别名
+    pub fn aliases_of(&self, name: &str) -> Vec<&str> {
+        let mut v: Vec<&str> = self
+            .aliases
+            .iter()
+            .filter(|(_, target)| **target == name)
+            .map(|(alias, _)| alias.as_str())
+            .collect();
+        v.sort();
+        v
+    }
+
+    /// 注册全部命令（按 `permission()` 设置根节点的 `requires`），再把别名注册为重定向到目标命令的字面量。
+    /// brigadier 的重定向只转发子节点，目标自身的执行体与权限要另外复制，别名单独使用时才能执行。
+    pub fn register_all(&self, d: &mut CommandDispatcher<CommandSource>) {
+        for c in &self.cmds {
+            c.register(d);
+
+            let perm = c.permission();
+            if perm > Permission::Everyone
+                && let Some(node) = d.root.read().child(c.name())
+            {
+                node.write().requirement = Arc::new(move |s: &CommandSource| s.permission() >= perm);
             }
-        })
+        }
+
+        for (alias, target) in &self.aliases {
+            let Some(target) = d.root.read().child(target) else {
+                continue;
+            };
+            let node = d.register(literal(alias).redirect(target.clone()));
+            let target = target.read();
+            let mut node = node.write();
+            node.command = target.command.clone();
+            node.requirement = target.requirement.clone();
+        }
     }
 
     pub fn all(&self) -> &[Arc<dyn BotCommand>] {
@@ -56,3 +206,131 @@ impl CommandRegistry {
             .join(", ")
     }
 }
+
+/// 合并内置别名与配置中的别名；与命令名重名、或同一别名指向不同命令时报错
+fn resolve_aliases(
+    cmds: &[Arc<dyn BotCommand>],
+    configured: &HashMap<String, String>,
+) -> Result<HashMap<String, &'static str>> {
+    let mut out: HashMap<String, &'static str> = HashMap::new();
+
+    let builtin = cmds
+        .iter()
+        .flat_map(|c| c.aliases().iter().map(move |a| (a.to_string(), c.name())));
+    let mut extra = vec![];
+    for (alias, target) in configured {
+        let Some(c) = cmds.iter().find(|c| c.name().eq_ignore_ascii_case(target.trim())) else {
+            bail!("alias `{alias}` points to unknown command `{target}`");
+        };
+        extra.push((alias.clone(), c.name()));
+    }
+
+    for (alias, target) in builtin.chain(extra) {
+        let alias = alias.trim().to_lowercase();
+        if alias.is_empty() || alias.contains(char::is_whitespace) {
+            bail!("invalid alias `{alias}` for command `{target}`");
+        }
+        if let Some(c) = cmds.iter().find(|c| c.name() == alias) {
+            bail!("alias `{alias}` of `{target}` collides with command `{}`", c.name());
+        }
+        match out.get(&alias) {
+            Some(prev) if *prev != target => {
+                bail!("alias `{alias}` is claimed by both `{prev}` and `{target}`")
+            }
+            _ => {
+                out.insert(alias, target);
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::command_processor::TestBot;
+    use crate::model::{ChatPlatform, MessageIn};
+
+    struct Fake(&'static str, &'static [&'static str]);
+
+    impl BotCommand for Fake {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+        fn description(&self, _lang: &str) -> String {
+            String::new()
+        }
+        fn usage(&self, _lang: &str) -> String {
+            String::new()
+        }
+        fn aliases(&self) -> &'static [&'static str] {
+            self.1
+        }
+        fn register(&self, d: &mut CommandDispatcher<CommandSource>) {
+            d.register(literal(self.0).executes(|_| 1));
+        }
+    }
+
+    fn cmds() -> Vec<Arc<dyn BotCommand>> {
+        vec![
+            Arc::new(Fake("ping", &["p"])),
+            Arc::new(Fake("github", &["gh"])),
+        ]
+    }
+
+    fn configured(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(a, t)| (a.to_string(), t.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn merges_builtin_and_configured_aliases() {
+        let aliases =
+            resolve_aliases(&cmds(), &configured(&[("Repo", "GitHub"), ("p", "ping")])).unwrap();
+        assert_eq!(aliases.get("p"), Some(&"ping"));
+        assert_eq!(aliases.get("gh"), Some(&"github"));
+        assert_eq!(aliases.get("repo"), Some(&"github"));
+    }
+
+    #[test]
+    fn rejects_alias_claimed_by_two_commands() {
+        let err = resolve_aliases(&cmds(), &configured(&[("p", "github")])).unwrap_err();
+        assert!(err.to_string().contains("claimed by both"), "{err}");
+    }
+
+    #[test]
+    fn rejects_alias_shadowing_a_command() {
+        let err = resolve_aliases(&cmds(), &configured(&[("ping", "github")])).unwrap_err();
+        assert!(
+            err.to_string().contains("collides with command `ping`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_aliases() {
+        assert!(resolve_aliases(&cmds(), &configured(&[("x", "nope")])).is_err());
+        assert!(resolve_aliases(&cmds(), &configured(&[("a b", "ping")])).is_err());
+        assert!(resolve_aliases(&cmds(), &configured(&[(" ", "ping")])).is_err());
+    }
+
+    /// 别名重定向复制目标的 `requires`，普通成员不能借别名执行管理命令
+    #[tokio::test]
+    async fn alias_inherits_target_permission() {
+        let mut props = AppProperties::default();
+        props.commands.aliases = configured(&[("sw", "switch")]);
+        let bot = TestBot::new(props).await;
+
+        let mut d = CommandDispatcher::<CommandSource>::new();
+        bot.registry.register_all(&mut d);
+        let alias = d.root.read().child("sw").unwrap();
+        let alias = alias.read();
+
+        let group = Address::new(ChatPlatform::Onebot, 1i64, true);
+        let src = CommandSource::new(MessageIn::new(group, 2i64, "/sw".to_string()));
+        assert!(!alias.can_use(&src));
+        assert!(alias.can_use(&src.clone().with_permission(Permission::ChatAdmin)));
+    }
+}
@@ -1,18 +1,31 @@
+use std::future::Future;
 use std::sync::{Arc, Mutex};
+use tokio::task::JoinSet;
+use tracing::error;
 
-use crate::model::{Attachment, MessageIn, MessageOut};
+use crate::core::markup::parse_markdown;
+use crate::core::paginator::Paginator;
+use crate::core::permission::Permission;
+use crate::i18n;
+use crate::model::{Attachment, MessageIn, MessageOut, Segment};
 
 #[derive(Clone)]
 pub struct CommandSource {
     in_msg: MessageIn,
+    permission: Permission,
+    lang: &'static str,
     outs: Arc<Mutex<Vec<MessageOut>>>,
+    tasks: Arc<Mutex<JoinSet<()>>>,
 }
 
 impl CommandSource {
     pub fn new(in_msg: MessageIn) -> Self {
         Self {
             in_msg,
+            permission: Permission::Everyone,
+            lang: i18n::FALLBACK,
             outs: Arc::new(Mutex::new(Vec::new())),
+            tasks: Arc::new(Mutex::new(JoinSet::new())),
         }
     }
 
@@ -20,6 +33,26 @@ impl CommandSource {
         &self.in_msg
     }
 
+    pub fn with_permission(mut self, permission: Permission) -> Self {
+        self.permission = permission;
+        self
+    }
+
+    /// 发送者在当前会话中的权限，供 brigadier `requires` 判断
+    pub fn permission(&self) -> Permission {
+        self.permission
+    }
+
+    pub fn with_lang(mut self, lang: &'static str) -> Self {
+        self.lang = lang;
+        self
+    }
+
+    /// 回复所用的语言，见 `ChatSettingsStore::language_for`
+    pub fn lang(&self) -> &'static str {
+        self.lang
+    }
+
     pub fn reply(&self, text: impl Into<String>) {
         self.outs
             .lock()
@@ -27,50 +60,88 @@ impl CommandSource {
             .push(MessageOut::text(self.in_msg.addr.clone(), text));
     }
 
+    /// 按简易 markdown（`code`、**bold**、```block```）解析后回复
+    pub fn reply_markdown(&self, text: impl AsRef<str>) {
+        self.reply_segments(parse_markdown(text.as_ref()));
+    }
+
+    pub fn reply_segments(&self, segments: Vec<Segment>) {
+        self.reply_out(MessageOut::new(self.in_msg.addr.clone(), segments));
+    }
+
+    /// 分页回复：先发第一页，支持按钮的平台附带翻页按钮并原地编辑
+    pub fn reply_pages(&self, pages: Vec<Vec<Segment>>) {
+        self.reply_out(Paginator::first_page(self.in_msg.addr.clone(), pages));
+    }
+
     pub fn reply_out(&self, out: MessageOut) {
         self.outs.lock().unwrap().push(out);
     }
 
+    // 附件回复供命令使用，内置命令暂未发送图片或文件
+    #[allow(dead_code)]
     pub fn reply_image_url(&self, url: impl Into<String>) {
-        self.reply_out(MessageOut {
-            addr: self.in_msg.addr.clone(),
-            text: None,
-            attachments: vec![Attachment::image_url(url)],
-        });
+        self.reply_out(MessageOut::attachment(
+            self.in_msg.addr.clone(),
+            Attachment::image_url(url),
+        ));
     }
 
+    #[allow(dead_code)]
     pub fn reply_image_bytes(
         &self,
         name: impl Into<String>,
         bytes: Vec<u8>,
         mime: impl Into<String>,
     ) {
-        self.reply_out(MessageOut {
-            addr: self.in_msg.addr.clone(),
-            text: None,
-            attachments: vec![Attachment::image_bytes(name, bytes, mime)],
-        });
+        self.reply_out(MessageOut::attachment(
+            self.in_msg.addr.clone(),
+            Attachment::image_bytes(name, bytes, mime),
+        ));
     }
 
+    #[allow(dead_code)]
     pub fn reply_file_url(&self, name: impl Into<String>, url: impl Into<String>) {
-        self.reply_out(MessageOut {
-            addr: self.in_msg.addr.clone(),
-            text: None,
-            attachments: vec![Attachment::file_url(name, url)],
-        });
+        self.reply_out(MessageOut::attachment(
+            self.in_msg.addr.clone(),
+            Attachment::file_url(name, url),
+        ));
     }
 
+    #[allow(dead_code)]
     pub fn reply_file_bytes(
         &self,
         name: impl Into<String>,
         bytes: Vec<u8>,
         mime: impl Into<String>,
     ) {
-        self.reply_out(MessageOut {
-            addr: self.in_msg.addr.clone(),
-            text: None,
-            attachments: vec![Attachment::file_bytes(name, bytes, mime)],
-        });
+        self.reply_out(MessageOut::attachment(
+            self.in_msg.addr.clone(),
+            Attachment::file_bytes(name, bytes, mime),
+        ));
+    }
+
+    /// 在命令上下文中启动异步任务；`CommandProcessor` 会在收集输出前等待这些任务完成。
+    pub fn spawn(&self, fut: impl Future<Output = ()> + Send + 'static) {
+        self.tasks.lock().unwrap().spawn(fut);
+    }
+
+    /// 等待所有已启动的任务（包括任务中再次 spawn 的任务）结束。
+    /// 若该 future 被丢弃（例如超时），尚未完成的任务会随 `JoinSet` 一起被 abort。
+    pub async fn join_tasks(&self) {
+        loop {
+            let mut set = std::mem::take(&mut *self.tasks.lock().unwrap());
+            if set.is_empty() {
+                return;
+            }
+            while let Some(res) = set.join_next().await {
+                if let Err(e) = res
+                    && e.is_panic()
+                {
+                    error!("command task panicked: {e:?}");
+                }
+            }
+        }
     }
 
     pub fn take_outs(&self) -> Vec<MessageOut> {
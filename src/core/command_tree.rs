@@ -0,0 +1,94 @@
+// src/core/command_tree.rs
+// 把 BotCommand 注册的 brigadier 节点导出为平台无关的树，供原生斜杠命令等使用
+use azalea_brigadier::builder::argument_builder::ArgumentBuilderType;
+use azalea_brigadier::tree::CommandNode;
+
+use crate::core::command_registry::BotCommand;
+use crate::core::command_source::CommandSource;
+use crate::core::dispatcher::CommandDispatcher;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    Text,
+    Integer,
+    Number,
+    Boolean,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Literal,
+    Argument(ArgKind),
+}
+
+#[derive(Debug, Clone)]
+pub struct TreeNode {
+    pub name: String,
+    pub kind: NodeKind,
+    /// 输入到此节点即可执行
+    pub executable: bool,
+    pub children: Vec<TreeNode>,
+}
+
+/// 在独立的 dispatcher 上注册该命令，取出以命令名为根的子树
+pub fn command_tree(cmd: &dyn BotCommand) -> Option<TreeNode> {
+    let mut d = CommandDispatcher::<CommandSource>::new();
+    cmd.register(&mut d);
+
+    let root = d.root.read();
+    let node = root.children.get(cmd.name())?;
+    Some(walk(&node.read()))
+}
+
+fn walk(node: &CommandNode<CommandSource>) -> TreeNode {
+    let (name, kind) = match &node.value {
+        ArgumentBuilderType::Literal(l) => (l.value.clone(), NodeKind::Literal),
+        ArgumentBuilderType::Argument(a) => {
+            (a.name.clone(), NodeKind::Argument(kind_of(&a.examples())))
+        }
+    };
+
+    TreeNode {
+        name,
+        kind,
+        executable: node.command.is_some(),
+        children: node.children.values().map(|c| walk(&c.read())).collect(),
+    }
+}
+
+/// brigadier 不公开参数的解析器，只能按其示例值推断类型
+fn kind_of(examples: &[String]) -> ArgKind {
+    let all = |f: fn(&str) -> bool| !examples.is_empty() && examples.iter().all(|e| f(e));
+
+    if all(|e| e == "true" || e == "false") {
+        ArgKind::Boolean
+    } else if all(|e| e.parse::<i64>().is_ok()) {
+        ArgKind::Integer
+    } else if all(|e| e.parse::<f64>().is_ok()) {
+        ArgKind::Number
+    } else {
+        ArgKind::Text
+    }
+}
+
+/// brigadier 的智能用法：每个直接子分支一行，形如 `github search <query>`
+pub fn smart_usage(cmd: &dyn BotCommand, source: &CommandSource) -> Vec<String> {
+    let mut d = CommandDispatcher::<CommandSource>::new();
+    cmd.register(&mut d);
+
+    let Some(node) = d.find_node(&[cmd.name()]) else {
+        return vec![];
+    };
+    let node = node.read();
+
+    let mut lines = vec![];
+    if node.command.is_some() {
+        lines.push(cmd.name().to_string());
+    }
+    lines.extend(
+        d.get_smart_usage(&node, source)
+            .into_iter()
+            .map(|(_, usage)| format!("{} {usage}", cmd.name())),
+    );
+    lines
+}
@@ -0,0 +1,42 @@
+// src/core/handler_processor.rs
+// 把普通消息交给匹配的 MessageHandler
+use std::sync::Arc;
+
+use tracing::warn;
+
+use crate::core::command_registry::CommandRegistry;
+use crate::model::{MessageIn, MessageOut};
+
+pub struct HandlerProcessor {
+    registry: Arc<CommandRegistry>,
+}
+
+impl HandlerProcessor {
+    pub fn new(registry: Arc<CommandRegistry>) -> Self {
+        Self { registry }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.registry.handlers().is_empty()
+    }
+
+    pub async fn handle(&self, input: &MessageIn) -> Vec<MessageOut> {
+        // 不响应其他 bot，避免互相触发
+        if input.sender.is_bot || input.text.trim().is_empty() {
+            return vec![];
+        }
+
+        let lang = self.registry.settings().language_for(input);
+        for h in self.registry.handlers() {
+            if !h.matcher().is_match(input) {
+                continue;
+            }
+            match h.handle(input, lang).await {
+                Ok(outs) if !outs.is_empty() => return outs,
+                Ok(_) => {}
+                Err(e) => warn!("handler {} failed: {e:?}", h.name()),
+            }
+        }
+        vec![]
+    }
+}
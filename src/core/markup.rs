@@ -0,0 +1,108 @@
+use crate::model::Segment;
+
+/// 把命令里常见的简易 markdown（```代码块```、`行内代码`、**粗体**）拆成 `Segment`，
+/// 其余内容原样作为文本，交给各平台渲染器转义。
+pub fn parse_markdown(s: &str) -> Vec<Segment> {
+    let mut out: Vec<Segment> = vec![];
+    let mut text = String::new();
+    let mut rest = s;
+
+    while !rest.is_empty() {
+        if let Some(body) = rest.strip_prefix("```")
+            && let Some(end) = body.find("```")
+        {
+            flush(&mut out, &mut text);
+            let block = &body[..end];
+            let (lang, code) = match block.split_once('\n') {
+                Some((l, c)) if !l.trim().contains(' ') => (l.trim(), c),
+                _ => ("", block),
+            };
+            out.push(Segment::CodeBlock {
+                lang: Some(lang.to_string()).filter(|l| !l.is_empty()),
+                code: code.trim_end_matches('\n').to_string(),
+            });
+            rest = body[end + 3..].strip_prefix('\n').unwrap_or(&body[end + 3..]);
+            continue;
+        }
+
+        if let Some(body) = rest.strip_prefix("**")
+            && let Some(end) = body.find("**")
+            && end > 0
+        {
+            flush(&mut out, &mut text);
+            out.push(Segment::Bold(body[..end].to_string()));
+            rest = &body[end + 2..];
+            continue;
+        }
+
+        if let Some(body) = rest.strip_prefix('`')
+            && let Some(end) = body.find(['`', '\n'])
+            && end > 0
+            && body[end..].starts_with('`')
+        {
+            flush(&mut out, &mut text);
+            out.push(Segment::Code(body[..end].to_string()));
+            rest = &body[end + 1..];
+            continue;
+        }
+
+        let ch = rest.chars().next().unwrap_or_default();
+        text.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+
+    flush(&mut out, &mut text);
+    out
+}
+
+fn flush(out: &mut Vec<Segment>, text: &mut String) {
+    if !text.is_empty() {
+        out.push(Segment::Text(std::mem::take(text)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 把片段写成紧凑的字符串便于比较
+    fn show(segs: &[Segment]) -> Vec<String> {
+        segs.iter()
+            .map(|s| match s {
+                Segment::Text(t) => format!("T:{t}"),
+                Segment::Bold(t) => format!("B:{t}"),
+                Segment::Code(t) => format!("C:{t}"),
+                Segment::CodeBlock { lang, code } => {
+                    format!("P:{}:{code}", lang.as_deref().unwrap_or(""))
+                }
+                other => format!("{other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bold_code_and_blocks() {
+        let segs = parse_markdown("a **b** `c` d\n```rust\nfn x() {}\n```\ntail");
+        assert_eq!(
+            show(&segs),
+            ["T:a ", "B:b", "T: ", "C:c", "T: d\n", "P:rust:fn x() {}", "T:tail"]
+        );
+
+        let segs = parse_markdown("```\nno lang here\n```");
+        assert_eq!(show(&segs), ["P::no lang here"]);
+        // 首行含空格时不当作语言名
+        let segs = parse_markdown("```a b\nc```");
+        assert_eq!(show(&segs), ["P::a b\nc"]);
+    }
+
+    /// 不成对的标记原样保留，交给渲染器转义
+    #[test]
+    fn unmatched_markers_stay_text() {
+        assert_eq!(show(&parse_markdown("2 ** 3")), ["T:2 ** 3"]);
+        assert_eq!(show(&parse_markdown("****")), ["T:****"]);
+        assert_eq!(show(&parse_markdown("`a\nb` <x> & _y_")), ["T:`a\nb` <x> & _y_"]);
+        assert_eq!(show(&parse_markdown("```open")), ["T:```open"]);
+        assert_eq!(show(&parse_markdown("中文**粗**`码`")), ["T:中文", "B:粗", "C:码"]);
+        assert!(parse_markdown("").is_empty());
+    }
+}
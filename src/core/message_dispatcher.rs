@@ -5,27 +5,32 @@ use std::{
 };
 use std::time::Instant;
 use tokio::sync::{mpsc, Mutex as AsyncMutex};
-use tracing::info;
+use tracing::{debug, info};
 
 use crate::core::message_sender_hub::MessageSenderHub;
+use crate::core::paginator::Paginator;
 use crate::core::pipeline_processor::PipelineProcessor;
-use crate::model::MessageIn;
+use crate::model::{ChatKey, MessageIn};
 
 #[derive(Clone)]
 pub struct MessageDispatcher {
     pipeline: Arc<PipelineProcessor>,
     hub: MessageSenderHub,
-    prefix: Option<String>,
+    paginator: Arc<Paginator>,
     running: Arc<AtomicBool>,
-    chat_locks: Arc<Mutex<HashMap<i64, Arc<AsyncMutex<()>>>>>,
+    chat_locks: Arc<Mutex<HashMap<ChatKey, Arc<AsyncMutex<()>>>>>,
 }
 
 impl MessageDispatcher {
-    pub fn new(pipeline: PipelineProcessor, hub: MessageSenderHub, prefix: String) -> Self {
+    pub fn new(
+        pipeline: PipelineProcessor,
+        hub: MessageSenderHub,
+        paginator: Arc<Paginator>,
+    ) -> Self {
         Self {
             pipeline: Arc::new(pipeline),
             hub,
-            prefix: Some(prefix),
+            paginator,
             running: Arc::new(AtomicBool::new(true)),
             chat_locks: Arc::new(Mutex::new(HashMap::new())),
         }
@@ -41,36 +46,45 @@ impl MessageDispatcher {
                 break;
             };
 
-            if let Some(prefix) = &self.prefix {
-                let t = input.text.trim();
-                if !t.starts_with(prefix) {
-                    continue;
+            if input.callback.is_some() {
+                self.on_callback(input);
+                continue;
+            }
+
+            if !self.pipeline.wants(&input) {
+                if input.addr.reply_token.is_some() {
+                    let hub = self.hub.clone();
+                    tokio::spawn(async move { hub.discard(&input.addr).await });
                 }
+                continue;
             }
 
             let hub = self.hub.clone();
             let pipeline = self.pipeline.clone();
-            let chat_id = input.addr.chat_id;
-
-            let lock = {
-                let mut m = self.chat_locks.lock().unwrap();
-                m.entry(chat_id)
-                    .or_insert_with(|| Arc::new(AsyncMutex::new(())))
-                    .clone()
-            };
+            let lock = self.chat_lock(input.addr.key());
 
             tokio::spawn(async move {
                 info!(
-                    "IN <- [{:?}] user={:?} chat={} text=\"{}\"",
-                    input.addr.platform, input.user_id, input.addr.chat_id, input.text
+                    "IN <- [{:?}] user={:?} ({}) chat={} text=\"{}\" attachments={:?}",
+                    input.addr.platform,
+                    input.user_id,
+                    input.sender.display_name.as_deref().unwrap_or("-"),
+                    input.addr.chat_id,
+                    input.text,
+                    input.attachments.iter().map(|a| a.ty).collect::<Vec<_>>()
                 );
 
                 let t0 = Instant::now();
-                let outs = pipeline.handle(input);
+                let addr = input.addr.clone();
+                let outs = pipeline.handle(input).await;
                 let cost_ms = t0.elapsed().as_millis();
 
                 if outs.is_empty() {
                     info!("PIPELINE result: empty ({} ms)", cost_ms);
+                    // 被黑名单 / 限流等静默拦下时，平台上不能一直挂着占位
+                    if addr.reply_token.is_some() {
+                        hub.discard(&addr).await;
+                    }
                     return;
                 }
 
@@ -85,4 +99,29 @@ impl MessageDispatcher {
             });
         }
     }
+
+    /// 按钮回调目前只有分页使用；编辑同样需要拿会话锁，避免与普通回复交错
+    fn on_callback(&self, input: MessageIn) {
+        let Some((message_id, out)) = self.paginator.on_press(&input) else {
+            debug!(
+                "CALLBACK ignored: [{:?}] chat={} data={:?}",
+                input.addr.platform, input.addr.chat_id, input.callback
+            );
+            return;
+        };
+
+        let hub = self.hub.clone();
+        let lock = self.chat_lock(input.addr.key());
+        tokio::spawn(async move {
+            let _g = lock.lock().await;
+            hub.edit(&message_id, out).await;
+        });
+    }
+
+    fn chat_lock(&self, key: ChatKey) -> Arc<AsyncMutex<()>> {
+        let mut m = self.chat_locks.lock().unwrap();
+        m.entry(key)
+            .or_insert_with(|| Arc::new(AsyncMutex::new(())))
+            .clone()
+    }
 }
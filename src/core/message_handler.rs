@@ -0,0 +1,97 @@
+// src/core/message_handler.rs
+// 不带命令前缀的消息处理器：按正则或谓词匹配普通文本
+use anyhow::Result;
+use async_trait::async_trait;
+use regex::Regex;
+
+use crate::model::{MessageIn, MessageOut};
+
+pub enum Matcher {
+    /// 在消息文本中查找匹配
+    Regex(Regex),
+    /// 按附件、提及等文本以外的条件匹配；内置处理器暂只用正则
+    #[allow(dead_code)]
+    Predicate(Box<dyn Fn(&MessageIn) -> bool + Send + Sync>),
+}
+
+impl Matcher {
+    pub fn is_match(&self, input: &MessageIn) -> bool {
+        match self {
+            Matcher::Regex(re) => re.is_match(&input.text),
+            Matcher::Predicate(f) => f(input),
+        }
+    }
+}
+
+/// 与 `BotCommand` 并列的第二类处理器。多个处理器匹配同一条消息时按 `priority()`
+/// 从高到低尝试，第一个产生输出的处理器生效。
+#[async_trait]
+pub trait MessageHandler: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    fn matcher(&self) -> &Matcher;
+
+    /// 每次触发消耗的限流令牌；`None` 表示使用全局 `default-cost`
+    fn cost(&self) -> Option<u32> {
+        None
+    }
+
+    /// 返回空列表表示不处理，交给下一个处理器；`lang` 为回复所用的语言
+    async fn handle(&self, input: &MessageIn, lang: &str) -> Result<Vec<MessageOut>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Address, ChatPlatform, InAttachment, InContentType};
+
+    /// 收到图片时回复，不看文本
+    struct PhotoHandler {
+        matcher: Matcher,
+    }
+
+    #[async_trait]
+    impl MessageHandler for PhotoHandler {
+        fn name(&self) -> &'static str {
+            "photo"
+        }
+
+        fn matcher(&self) -> &Matcher {
+            &self.matcher
+        }
+
+        async fn handle(&self, input: &MessageIn, _lang: &str) -> Result<Vec<MessageOut>> {
+            Ok(vec![MessageOut::text(input.addr.clone(), "nice photo")])
+        }
+    }
+
+    #[tokio::test]
+    async fn predicate_matcher_fires_on_non_text_condition() {
+        let h = PhotoHandler {
+            matcher: Matcher::Predicate(Box::new(|m| {
+                m.attachments.iter().any(|a| a.ty == InContentType::Photo)
+            })),
+        };
+        let addr = Address::new(ChatPlatform::Telegram, 1i64, true);
+
+        let plain = MessageIn::new(addr.clone(), 2i64, "photo".to_string());
+        assert!(!h.matcher().is_match(&plain));
+
+        let mut photo = MessageIn::new(addr, 2i64, String::new());
+        photo.attachments.push(InAttachment {
+            ty: InContentType::Photo,
+            file_id: "f".into(),
+            name: None,
+            mime: None,
+            size: None,
+            url: None,
+        });
+        assert!(h.matcher().is_match(&photo));
+        let outs = h.handle(&photo, "en-us").await.unwrap();
+        assert_eq!(outs[0].plain_text(), "nice photo");
+    }
+}
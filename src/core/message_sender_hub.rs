@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use std::{
     collections::HashMap,
@@ -6,22 +6,50 @@ use std::{
 };
 use tracing::{error, info, warn};
 
-use crate::model::{Attachment, ChatPlatform, MessageOut};
+use crate::core::chunker;
+use crate::core::paginator::Paginator;
+use crate::model::{Address, ChatId, ChatPlatform, MessageOut};
 
+/// 各平台发送器；超长文本由实现方通过 `chunker::split` 按本平台上限拆分
 #[async_trait]
 pub trait Sender: Send + Sync {
-    async fn send(&self, out: MessageOut) -> Result<()>;
+    /// 返回最后一条已发送消息的 id，供分页等后续编辑使用
+    async fn send(&self, out: MessageOut) -> Result<Option<ChatId>>;
+
+    /// 原地编辑已发送的消息（文本与按钮）
+    async fn edit(&self, _message_id: &ChatId, _out: MessageOut) -> Result<()> {
+        Err(anyhow!("edit is not supported"))
+    }
+
+    /// 带 `reply_token` 的输入没有产生任何输出时调用，撤掉平台预先显示的占位（如 Discord 的“思考中”）
+    async fn discard(&self, _addr: &Address) -> Result<()> {
+        Ok(())
+    }
+
+    /// 不支持内联按钮的平台会把分页结果逐页发出
+    fn supports_buttons(&self) -> bool {
+        false
+    }
+
+    /// 用户是否为该群 / 服务器的管理员；不支持查询的平台返回 `false`
+    async fn is_chat_admin(&self, _addr: &Address, _user_id: i64) -> Result<bool> {
+        Ok(false)
+    }
 }
 
 #[derive(Clone)]
 pub struct MessageSenderHub {
     senders: Arc<Mutex<HashMap<ChatPlatform, Arc<dyn Sender>>>>,
+    long_text_file_threshold: usize,
+    paginator: Arc<Paginator>,
 }
 
 impl MessageSenderHub {
-    pub fn new() -> Self {
+    pub fn new(long_text_file_threshold: usize, paginator: Arc<Paginator>) -> Self {
         Self {
             senders: Arc::new(Mutex::new(HashMap::new())),
+            long_text_file_threshold,
+            paginator,
         }
     }
 
@@ -49,9 +77,37 @@ impl MessageSenderHub {
         }
     }
 
+    pub async fn edit(&self, message_id: &ChatId, out: MessageOut) {
+        let p = out.addr.platform;
+        let Some(sender) = self.sender_of(p) else {
+            warn!("No Sender for platform: {:?}", p);
+            return;
+        };
+
+        info!(
+            "EDIT -> [{:?}] chat={} message={}",
+            p, out.addr.chat_id, message_id
+        );
+        if let Err(e) = sender.edit(message_id, out).await {
+            error!("Edit failed on platform {:?}: {:?}", p, e);
+        }
+    }
+
+    pub async fn discard(&self, addr: &Address) {
+        let p = addr.platform;
+        let Some(sender) = self.sender_of(p) else {
+            warn!("No Sender for platform: {:?}", p);
+            return;
+        };
+        if let Err(e) = sender.discard(addr).await {
+            error!("Discard failed on platform {:?}: {:?}", p, e);
+        }
+    }
+
     async fn send_one(&self, out: MessageOut) -> Result<()> {
-        let att = out.attachments.len();
-        let text = out.text.as_deref().unwrap_or("");
+        let mut out = chunker::text_as_file(out, self.long_text_file_threshold);
+        let att = out.attachments().count();
+        let text = out.plain_text();
 
         info!(
             "OUT -> [{:?}] to chat={} text=\"{}\" attachments={}",
@@ -59,17 +115,39 @@ impl MessageSenderHub {
         );
 
         let p = out.addr.platform;
-        let s = { self.senders.lock().unwrap().get(&p).cloned() };
-
-        let Some(sender) = s else {
+        let Some(sender) = self.sender_of(p) else {
             warn!("No Sender for platform: {:?}", p);
             return Ok(());
         };
 
-        if let Err(e) = sender.send(out).await {
-            error!("Send failed on platform {:?}: {:?}", p, e);
+        let pages = std::mem::take(&mut out.pages);
+        if !pages.is_empty() && !sender.supports_buttons() {
+            // 没有按钮就把剩余页依次发出
+            let addr = out.addr.clone();
+            out.keyboard.clear();
+            let rest = pages
+                .into_iter()
+                .skip(1)
+                .map(|segs| MessageOut::new(addr.clone(), segs));
+            for o in std::iter::once(out).chain(rest) {
+                if let Err(e) = sender.send(o).await {
+                    error!("Send failed on platform {:?}: {:?}", p, e);
+                }
+            }
+            return Ok(());
+        }
+
+        let addr = out.addr.clone();
+        match sender.send(out).await {
+            Ok(Some(id)) if !pages.is_empty() => self.paginator.register(&addr, id, pages),
+            Ok(_) => {}
+            Err(e) => error!("Send failed on platform {:?}: {:?}", p, e),
         }
 
         Ok(())
     }
+
+    pub fn sender_of(&self, p: ChatPlatform) -> Option<Arc<dyn Sender>> {
+        self.senders.lock().unwrap().get(&p).cloned()
+    }
 }
@@ -0,0 +1,32 @@
+// src/core/middleware.rs
+// 管线中间件：命令处理前后依次经过的处理阶段
+use async_trait::async_trait;
+
+use crate::model::{MessageIn, MessageOut};
+
+pub enum Flow {
+    /// 交给下一个阶段
+    Continue,
+    /// 不再继续，以这些消息作为结果（可为空，即丢弃该消息）
+    Stop(Vec<MessageOut>),
+}
+
+/// 按 `[pipeline] middlewares` 的顺序执行 `before`，命令处理后按相反顺序执行 `after`。
+/// 某个阶段返回 `Stop` 时，只有它及之前的阶段会执行 `after`。
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// 需要处理不带命令前缀的普通消息时返回 `true`
+    fn wants_plain_text(&self) -> bool {
+        false
+    }
+
+    /// 分发前：过滤或改写输入
+    async fn before(&self, _input: &mut MessageIn) -> Flow {
+        Flow::Continue
+    }
+
+    /// 分发后：改写输出
+    async fn after(&self, _input: &MessageIn, _outs: &mut Vec<MessageOut>) {}
+}
@@ -1,12 +1,25 @@
+pub mod chat_settings;
+pub mod chunker;
 pub mod command_processor;
 pub mod command_registry;
 pub mod command_source;
+pub mod command_tree;
 pub mod dispatcher;
+pub mod handler_processor;
+pub mod markup;
 pub mod message_dispatcher;
+pub mod message_handler;
 pub mod message_sender_hub;
+pub mod middleware;
+pub mod paginator;
+pub mod permission;
+pub mod prefix;
 pub mod pipeline_processor;
+pub mod rate_limiter;
+pub mod syntax_error;
 
 pub use command_registry::CommandRegistry;
 pub use message_dispatcher::MessageDispatcher;
 pub use message_sender_hub::MessageSenderHub;
+pub use paginator::Paginator;
 pub use pipeline_processor::PipelineProcessor;
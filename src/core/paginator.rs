@@ -0,0 +1,166 @@
+// src/core/paginator.rs
+// 长结果分页：发送第一页并附带翻页按钮，按下时原地编辑为目标页
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::model::{Address, Button, ChatId, ChatKey, MessageIn, MessageOut, Segment};
+
+const DATA_PREFIX: &str = "page:";
+const DATA_NOOP: &str = "page:-";
+
+struct Entry {
+    pages: Arc<Vec<Vec<Segment>>>,
+    expires: Instant,
+}
+
+pub struct Paginator {
+    ttl: Duration,
+    entries: Mutex<HashMap<(ChatKey, ChatId), Entry>>,
+}
+
+impl Paginator {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 生成第一页的消息；只有一页时不带按钮。任何命令都可以通过 `CommandSource::reply_pages` 使用。
+    pub fn first_page(addr: Address, pages: Vec<Vec<Segment>>) -> MessageOut {
+        let mut out = page_out(addr, &pages, 0);
+        if pages.len() > 1 {
+            out.pages = pages;
+        }
+        out
+    }
+
+    /// 消息发出后按其 id 登记全部页，顺带清理过期条目
+    pub fn register(&self, addr: &Address, message_id: ChatId, pages: Vec<Vec<Segment>>) {
+        let now = Instant::now();
+        let mut m = self.entries.lock().unwrap();
+        m.retain(|_, e| e.expires > now);
+        m.insert(
+            (addr.key(), message_id),
+            Entry {
+                pages: Arc::new(pages),
+                expires: now + self.ttl,
+            },
+        );
+    }
+
+    /// 处理翻页按钮，返回要编辑的消息 id 和目标页；不是翻页回调、或结果已过期时返回 `None`
+    pub fn on_press(&self, input: &MessageIn) -> Option<(ChatId, MessageOut)> {
+        let index: usize = input
+            .callback
+            .as_deref()?
+            .strip_prefix(DATA_PREFIX)?
+            .parse()
+            .ok()?;
+        let message_id = input.message_id.clone()?;
+
+        let pages = {
+            let mut m = self.entries.lock().unwrap();
+            let key = (input.addr.key(), message_id.clone());
+            let e = m.get_mut(&key)?;
+            if e.expires <= Instant::now() {
+                m.remove(&key);
+                return None;
+            }
+            // 有人翻页就续期
+            e.expires = Instant::now() + self.ttl;
+            e.pages.clone()
+        };
+
+        if index >= pages.len() {
+            return None;
+        }
+        Some((message_id, page_out(input.addr.clone(), &pages, index)))
+    }
+}
+
+fn page_out(addr: Address, pages: &[Vec<Segment>], index: usize) -> MessageOut {
+    let mut out = MessageOut::new(addr, pages.get(index).cloned().unwrap_or_default());
+    if pages.len() <= 1 {
+        return out;
+    }
+
+    let mut row = vec![];
+    if index > 0 {
+        row.push(Button::new("◀", format!("{DATA_PREFIX}{}", index - 1)));
+    }
+    row.push(Button::new(format!("{}/{}", index + 1, pages.len()), DATA_NOOP));
+    if index + 1 < pages.len() {
+        row.push(Button::new("▶", format!("{DATA_PREFIX}{}", index + 1)));
+    }
+    out.keyboard = vec![row];
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ChatPlatform;
+
+    fn addr(chat_id: i64) -> Address {
+        Address::new(ChatPlatform::Telegram, chat_id, true)
+    }
+
+    fn pages(n: usize) -> Vec<Vec<Segment>> {
+        (1..=n).map(|i| vec![Segment::text(format!("page {i}"))]).collect()
+    }
+
+    fn press(addr: Address, message_id: i64, data: &str) -> MessageIn {
+        let mut input = MessageIn::new(addr, 1, String::new());
+        input.message_id = Some(message_id.into());
+        input.callback = Some(data.to_string());
+        input
+    }
+
+    fn labels(out: &MessageOut) -> Vec<&str> {
+        out.keyboard[0].iter().map(|b| b.label.as_str()).collect()
+    }
+
+    #[test]
+    fn single_page_has_no_buttons() {
+        let out = Paginator::first_page(addr(1), pages(1));
+        assert_eq!(out.plain_text(), "page 1");
+        assert!(out.keyboard.is_empty());
+        assert!(out.pages.is_empty());
+    }
+
+    #[test]
+    fn buttons_follow_the_current_page() {
+        let out = Paginator::first_page(addr(1), pages(3));
+        assert_eq!(out.pages.len(), 3);
+        assert_eq!(labels(&out), ["1/3", "▶"]);
+
+        let p = Paginator::new(Duration::from_secs(60));
+        p.register(&addr(1), 10i64.into(), pages(3));
+
+        let (id, out) = p.on_press(&press(addr(1), 10, "page:1")).unwrap();
+        assert_eq!(id, 10i64.into());
+        assert_eq!(out.plain_text(), "page 2");
+        assert_eq!(labels(&out), ["◀", "2/3", "▶"]);
+
+        let (_, out) = p.on_press(&press(addr(1), 10, "page:2")).unwrap();
+        assert_eq!(labels(&out), ["◀", "3/3"]);
+    }
+
+    #[test]
+    fn ignores_foreign_stale_and_invalid_presses() {
+        let p = Paginator::new(Duration::from_secs(60));
+        p.register(&addr(1), 10i64.into(), pages(2));
+
+        assert!(p.on_press(&press(addr(1), 10, DATA_NOOP)).is_none());
+        assert!(p.on_press(&press(addr(1), 10, "page:5")).is_none());
+        assert!(p.on_press(&press(addr(1), 11, "page:1")).is_none());
+        assert!(p.on_press(&press(addr(2), 10, "page:1")).is_none());
+        assert!(p.on_press(&press(addr(1), 10, "other:1")).is_none());
+
+        let expired = Paginator::new(Duration::ZERO);
+        expired.register(&addr(1), 10i64.into(), pages(2));
+        assert!(expired.on_press(&press(addr(1), 10, "page:1")).is_none());
+    }
+}
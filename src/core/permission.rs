@@ -0,0 +1,99 @@
+// src/core/permission.rs
+// 命令权限：所有人 / 会话管理员 / bot 所有者；管理员身份按平台查询并短暂缓存
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+
+use crate::config::AppProperties;
+use crate::core::message_sender_hub::MessageSenderHub;
+use crate::i18n::tr;
+use crate::model::{ChatKey, MessageIn};
+
+/// 管理员查询结果的缓存时间
+const ADMIN_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Permission {
+    #[default]
+    Everyone,
+    /// 群 / 服务器管理员；私聊中用户视为自己会话的管理员
+    ChatAdmin,
+    /// `owners` 中配置的 bot 所有者，拥有全部权限
+    Owner,
+}
+
+impl Permission {
+    pub fn label(self, lang: &str) -> String {
+        match self {
+            Permission::Everyone => tr!(lang, "permission.everyone"),
+            Permission::ChatAdmin => tr!(lang, "permission.chat-admin"),
+            Permission::Owner => tr!(lang, "permission.owner"),
+        }
+    }
+}
+
+pub struct PermissionResolver {
+    props: Arc<AppProperties>,
+    hub: MessageSenderHub,
+    admins: Mutex<HashMap<(ChatKey, i64), (bool, Instant)>>,
+}
+
+impl PermissionResolver {
+    pub fn new(props: Arc<AppProperties>, hub: MessageSenderHub) -> Self {
+        Self {
+            props,
+            hub,
+            admins: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 依次判断：所有者、私聊、事件自带的管理员身份、向平台查询（失败视为普通成员）
+    pub async fn level_of(&self, input: &MessageIn) -> Permission {
+        let Some(user_id) = input.user_id else {
+            return Permission::Everyone;
+        };
+        if self.props.owners_of(input.addr.platform).contains(&user_id) {
+            return Permission::Owner;
+        }
+        if !input.addr.is_group {
+            return Permission::ChatAdmin;
+        }
+
+        let is_admin = match input.sender.is_chat_admin {
+            Some(b) => b,
+            None => self.lookup(input, user_id).await,
+        };
+        if is_admin {
+            Permission::ChatAdmin
+        } else {
+            Permission::Everyone
+        }
+    }
+
+    async fn lookup(&self, input: &MessageIn, user_id: i64) -> bool {
+        let key = (input.addr.key(), user_id);
+        let now = Instant::now();
+        {
+            let mut m = self.admins.lock().unwrap();
+            m.retain(|_, (_, at)| now.duration_since(*at) < ADMIN_TTL);
+            if let Some((b, _)) = m.get(&key) {
+                return *b;
+            }
+        }
+
+        let Some(sender) = self.hub.sender_of(input.addr.platform) else {
+            return false;
+        };
+        let is_admin = match sender.is_chat_admin(&input.addr, user_id).await {
+            Ok(b) => b,
+            Err(e) => {
+                warn!("admin lookup failed for {} in {}: {e:?}", user_id, key.0);
+                return false;
+            }
+        };
+        self.admins.lock().unwrap().insert(key, (is_admin, now));
+        is_admin
+    }
+}
@@ -1,22 +1,141 @@
 use std::sync::Arc;
 
-use crate::config::AppProperties;
+use crate::config::{AppProperties, ReplyMode};
+use crate::core::chat_settings::ChatSettingsStore;
 use crate::core::command_processor::CommandProcessor;
 use crate::core::command_registry::CommandRegistry;
+use crate::core::handler_processor::HandlerProcessor;
+use crate::core::message_sender_hub::MessageSenderHub;
+use crate::core::middleware::{Flow, Middleware};
+use crate::core::prefix::Prefixes;
+use crate::middlewares;
 use crate::model::{MessageIn, MessageOut};
 
 pub struct PipelineProcessor {
+    settings: Arc<ChatSettingsStore>,
+    prefixes: Arc<Prefixes>,
+    chain: Vec<Arc<dyn Middleware>>,
     cmd: CommandProcessor,
+    handlers: HandlerProcessor,
 }
 
 impl PipelineProcessor {
-    pub fn new(props: Arc<AppProperties>, registry: Arc<CommandRegistry>) -> Self {
+    pub fn new(
+        props: Arc<AppProperties>,
+        registry: Arc<CommandRegistry>,
+        hub: MessageSenderHub,
+    ) -> Self {
         Self {
-            cmd: CommandProcessor::new(props, registry),
+            chain: middlewares::build_chain(&props, &registry),
+            settings: registry.settings().clone(),
+            prefixes: registry.prefixes().clone(),
+            handlers: HandlerProcessor::new(registry.clone()),
+            cmd: CommandProcessor::new(props, registry, hub),
         }
     }
 
-    pub fn handle(&self, input: MessageIn) -> Vec<MessageOut> {
-        self.cmd.handle(input)
+    /// 分发器据此预先丢弃无关消息；有普通消息处理器或中间件需要普通消息时全部接收
+    pub fn wants(&self, input: &MessageIn) -> bool {
+        !self.handlers.is_empty()
+            || self.chain.iter().any(|m| m.wants_plain_text())
+            || self.prefixes.is_command(input)
+    }
+
+    pub async fn handle(&self, mut input: MessageIn) -> Vec<MessageOut> {
+        let mut ran = 0;
+        let mut outs = None;
+        for m in &self.chain {
+            ran += 1;
+            if let Flow::Stop(o) = m.before(&mut input).await {
+                outs = Some(o);
+                break;
+            }
+        }
+
+        let mut outs = match outs {
+            Some(o) => o,
+            None if self.prefixes.is_command(&input) => {
+                self.cmd.handle(input.clone()).await
+            }
+            None => self.handlers.handle(&input).await,
+        };
+        for m in self.chain[..ran].iter().rev() {
+            m.after(&input, &mut outs).await;
+        }
+
+        // 只引用第一条，避免同一条消息的多条输出重复引用
+        let quote = match self.settings.get(&input.addr).reply {
+            ReplyMode::Always => input.message_id.clone(),
+            ReplyMode::Never => None,
+        };
+        if let (Some(id), Some(first)) = (quote, outs.first_mut())
+            && first.reply_to.is_none()
+        {
+            first.reply_to = Some(id);
+        }
+        outs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::chunker;
+    use crate::core::command_processor::TestBot;
+    use crate::core::message_sender_hub::Sender;
+    use crate::model::{Address, ChatId, ChatPlatform};
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// 像平台发送器一样按很小的上限拆分，记录实际发出的每一条
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<MessageOut>>);
+
+    #[async_trait]
+    impl Sender for Recorder {
+        async fn send(&self, out: MessageOut) -> Result<Option<ChatId>> {
+            let parts = chunker::split(out, 40, |segs| {
+                crate::model::plain_text(segs).chars().count()
+            });
+            self.0.lock().unwrap().extend(parts);
+            Ok(None)
+        }
+    }
+
+    async fn send_help(addr: Address, message_id: Option<i64>) -> Vec<MessageOut> {
+        let bot = TestBot::new(AppProperties::default()).await;
+        let recorder = Arc::new(Recorder::default());
+        bot.hub.register(ChatPlatform::Onebot, recorder.clone());
+        let pipeline = PipelineProcessor::new(bot.props, bot.registry, bot.hub.clone());
+
+        let mut input = MessageIn::new(addr, 42i64, "/help".to_string());
+        input.message_id = message_id.map(ChatId::from);
+        bot.hub.send_batch(pipeline.handle(input).await, true).await;
+
+        let parts = std::mem::take(&mut *recorder.0.lock().unwrap());
+        assert!(parts.len() > 1, "help should span several parts");
+        parts
+    }
+
+    /// 群聊默认引用触发消息，拆分后只有第一条带引用
+    #[tokio::test]
+    async fn quotes_only_the_first_chunk() {
+        let group = Address::new(ChatPlatform::Onebot, 1i64, true);
+        let parts = send_help(group, Some(7)).await;
+        assert_eq!(parts[0].reply_to, Some(ChatId::Int(7)));
+        assert!(parts[1..].iter().all(|m| m.reply_to.is_none()));
+    }
+
+    /// 私聊默认不引用；拿不到原消息 id 时群聊也照常发送，只是不带引用
+    #[tokio::test]
+    async fn sends_without_quote() {
+        let dm = Address::new(ChatPlatform::Onebot, 42i64, false);
+        let parts = send_help(dm, Some(7)).await;
+        assert!(parts.iter().all(|m| m.reply_to.is_none()));
+
+        let group = Address::new(ChatPlatform::Onebot, 1i64, true);
+        let parts = send_help(group, None).await;
+        assert!(parts.iter().all(|m| m.reply_to.is_none()));
     }
 }
@@ -0,0 +1,189 @@
+// src/core/prefix.rs
+// 命令前缀：按会话设置解析出的前缀列表 + 提及即前缀
+use std::sync::Arc;
+
+use crate::config::AppProperties;
+use crate::core::chat_settings::ChatSettingsStore;
+use crate::i18n::tr;
+use crate::model::{Address, MessageIn, Trigger};
+
+/// 单个前缀的最大字符数与每个会话最多的前缀数
+pub const MAX_PREFIX_LEN: usize = 8;
+pub const MAX_PREFIXES: usize = 8;
+
+/// 判断一条消息是否为命令、去掉前缀得到命令行，全部在这里完成
+pub struct Prefixes {
+    settings: Arc<ChatSettingsStore>,
+    mention: bool,
+}
+
+impl Prefixes {
+    pub fn new(props: &AppProperties, settings: Arc<ChatSettingsStore>) -> Self {
+        Self {
+            settings,
+            mention: props.mention_prefix,
+        }
+    }
+
+    /// 会话内生效的前缀，第一个为展示用的主前缀
+    pub fn of(&self, addr: &Address) -> Vec<String> {
+        self.settings.get(addr).prefixes
+    }
+
+    /// 用于回复中展示命令写法；没有任何前缀时为空串
+    pub fn primary(&self, addr: &Address) -> String {
+        self.of(addr).into_iter().next().unwrap_or_default()
+    }
+
+    pub fn mention_enabled(&self) -> bool {
+        self.mention
+    }
+
+    /// 返回（展示用前缀, 命令行）；不是命令或命令行为空时为 `None`。
+    /// 有多个前缀同时匹配时取最长的，如 `!!` 优先于 `!`。
+    /// 平台原生命令（`Trigger::Explicit`）已由适配器整理好命令行，不再剥前缀。
+    pub fn parse<'a>(&self, input: &'a MessageIn) -> Option<(String, &'a str)> {
+        let prefixes = self.of(&input.addr);
+        let text = input.text.trim();
+        let primary = || prefixes.first().cloned().unwrap_or_default();
+
+        let (prefix, line) = match input.trigger {
+            Trigger::Explicit => (primary(), text),
+            trigger => {
+                let matched = prefixes
+                    .iter()
+                    .filter(|p| text.starts_with(p.as_str()))
+                    .max_by_key(|p| p.len());
+                match matched {
+                    Some(p) => (p.clone(), &text[p.len()..]),
+                    // `@bot help` 与 `@bot /help` 都可以
+                    None if trigger == Trigger::Mention && self.mention => (primary(), text),
+                    None => return None,
+                }
+            }
+        };
+
+        let line = line.trim();
+        (!line.is_empty()).then_some((prefix, line))
+    }
+
+    /// 参数中的命令名可以带前缀，如 `/help /ping`
+    pub fn strip_name<'a>(&self, addr: &Address, name: &'a str) -> &'a str {
+        self.of(addr)
+            .iter()
+            .filter(|p| name.starts_with(p.as_str()))
+            .max_by_key(|p| p.len())
+            .map_or(name, |p| &name[p.len()..])
+    }
+
+    pub fn is_command(&self, input: &MessageIn) -> bool {
+        self.parse(input).is_some()
+    }
+}
+
+/// 解析空格分隔的前缀列表并去重；前缀不能含空白，长度与数量有上限
+pub fn parse_list(raw: &str, lang: &str) -> Result<Vec<String>, String> {
+    let mut out: Vec<String> = vec![];
+    for p in raw.split_whitespace() {
+        if p.chars().count() > MAX_PREFIX_LEN {
+            return Err(tr!(lang, "prefix.too-long", prefix = p, max = MAX_PREFIX_LEN));
+        }
+        if !out.iter().any(|q| q == p) {
+            out.push(p.to_string());
+        }
+    }
+    if out.is_empty() {
+        return Err(tr!(lang, "prefix.empty"));
+    }
+    if out.len() > MAX_PREFIXES {
+        return Err(tr!(lang, "prefix.too-many", max = MAX_PREFIXES));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ChatPlatform;
+    use crate::storage::Storage;
+
+    fn prefixes(list: &[&str], mention: bool) -> Prefixes {
+        let props = Arc::new(AppProperties {
+            prefixes: list.iter().map(|p| p.to_string()).collect(),
+            mention_prefix: mention,
+            ..AppProperties::default()
+        });
+        let settings = ChatSettingsStore::new(props.clone(), &Storage::in_memory());
+        Prefixes::new(&props, Arc::new(settings))
+    }
+
+    fn msg(text: &str, trigger: Trigger) -> MessageIn {
+        let addr = Address::new(ChatPlatform::Telegram, 1i64, true);
+        let mut input = MessageIn::new(addr, 7, text.to_string());
+        input.trigger = trigger;
+        input
+    }
+
+    fn parse(p: &Prefixes, text: &str, trigger: Trigger) -> Option<(String, String)> {
+        let input = msg(text, trigger);
+        p.parse(&input).map(|(prefix, line)| (prefix, line.to_string()))
+    }
+
+    fn pair(prefix: &str, line: &str) -> Option<(String, String)> {
+        Some((prefix.to_string(), line.to_string()))
+    }
+
+    #[test]
+    fn longest_prefix_wins_and_line_is_kept_verbatim() {
+        let p = prefixes(&["/", "!", "!!"], true);
+        assert_eq!(parse(&p, "/help", Trigger::None), pair("/", "help"));
+        assert_eq!(parse(&p, "!!ping", Trigger::None), pair("!!", "ping"));
+        assert_eq!(
+            parse(&p, r#"  ! say "a b" \"c\"  "#, Trigger::None),
+            pair("!", r#"say "a b" \"c\""#)
+        );
+        assert_eq!(parse(&p, "hello", Trigger::None), None);
+        assert_eq!(parse(&p, "/   ", Trigger::None), None);
+        assert_eq!(p.strip_name(&msg("", Trigger::None).addr, "!!ping"), "ping");
+        assert_eq!(p.primary(&msg("", Trigger::None).addr), "/");
+    }
+
+    #[test]
+    fn mention_acts_as_prefix_only_when_enabled() {
+        let p = prefixes(&["/"], true);
+        assert_eq!(parse(&p, "help", Trigger::Mention), pair("/", "help"));
+        assert_eq!(parse(&p, "/help", Trigger::Mention), pair("/", "help"));
+        assert_eq!(parse(&p, "", Trigger::Mention), None);
+
+        let p = prefixes(&["/"], false);
+        assert_eq!(parse(&p, "help", Trigger::Mention), None);
+        assert_eq!(parse(&p, "/help", Trigger::Mention), pair("/", "help"));
+    }
+
+    #[test]
+    fn explicit_commands_are_not_stripped() {
+        let p = prefixes(&["g"], false);
+        assert_eq!(
+            parse(&p, "github user octocat", Trigger::Explicit),
+            pair("g", "github user octocat")
+        );
+        assert_eq!(parse(&p, "github", Trigger::None), pair("g", "ithub"));
+
+        let p = prefixes(&[], false);
+        assert_eq!(parse(&p, "ping", Trigger::Explicit), pair("", "ping"));
+        assert_eq!(parse(&p, "ping", Trigger::None), None);
+    }
+
+    #[test]
+    fn parse_list_dedups_and_enforces_limits() {
+        let lang = crate::i18n::FALLBACK;
+        assert_eq!(parse_list(" ! . ! ", lang).unwrap(), vec!["!", "."]);
+        assert_eq!(parse_list("「 」", lang).unwrap(), vec!["「", "」"]);
+        assert!(parse_list("   ", lang).is_err());
+        assert!(parse_list(&"x".repeat(MAX_PREFIX_LEN + 1), lang).is_err());
+        assert!(parse_list(&"长".repeat(MAX_PREFIX_LEN), lang).is_ok());
+
+        let many: Vec<String> = (0..=MAX_PREFIXES).map(|i| format!("p{i}")).collect();
+        assert!(parse_list(&many.join(" "), lang).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+// src/core/rate_limiter.rs
+// 命令限流：按 (平台, 用户) 与 (平台, 会话) 两个令牌桶，再加上按命令的冷却时间
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::RateLimitConfig;
+use crate::model::{Address, ChatKey, ChatPlatform};
+
+/// 桶数量超过该值时清理已回满的桶（回满的桶与新建的等价）
+const PURGE_THRESHOLD: usize = 4096;
+/// 每处理这么多次请求也清理一次，过期的冷却与提示记录不会因为桶少而一直留着
+const PURGE_EVERY: u64 = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum BucketKey {
+    User(ChatPlatform, i64),
+    Chat(ChatKey),
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    /// 创建 / 上次更新时使用的参数；会话桶的参数可按会话设置
+    capacity: f64,
+    refill: f64,
+}
+
+/// 会话桶的参数，由 `ChatSettings` 按会话解析
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChatLimit {
+    /// 只控制会话桶；用户桶与冷却不受影响
+    pub enabled: bool,
+    pub capacity: f64,
+    pub refill: f64,
+}
+
+#[derive(Default)]
+struct State {
+    buckets: HashMap<BucketKey, Bucket>,
+    /// (平台, 用户, 命令) -> 冷却结束时间
+    cooldowns: HashMap<(ChatPlatform, i64, &'static str), Instant>,
+    /// (平台, 用户) -> 上次发送“请稍候”提示的时间
+    notices: HashMap<(ChatPlatform, i64), Instant>,
+    /// 自上次清理以来的请求数
+    since_purge: u64,
+}
+
+pub struct RateLimiter {
+    cfg: RateLimitConfig,
+    state: Mutex<State>,
+}
+
+impl RateLimiter {
+    pub fn new(cfg: RateLimitConfig) -> Self {
+        Self {
+            cfg,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub fn default_cost(&self) -> u32 {
+        self.cfg.default_cost
+    }
+
+    /// 尝试为一次命令扣除令牌。被限流时不扣除任何令牌，返回需要等待的时间。
+    /// 全局关闭限流时直接放行；会话内关闭（`chat.enabled == false`）只跳过会话桶。
+    pub fn acquire(
+        &self,
+        addr: &Address,
+        chat: ChatLimit,
+        user_id: Option<i64>,
+        command: &'static str,
+        cost: u32,
+        cooldown: Option<Duration>,
+    ) -> Option<Duration> {
+        if !self.cfg.enabled {
+            return None;
+        }
+        let now = Instant::now();
+        let mut st = self.state.lock().unwrap();
+        self.maybe_purge(&mut st, now);
+
+        let mut checks = vec![];
+        if chat.enabled {
+            checks.push((BucketKey::Chat(addr.key()), chat.capacity, chat.refill));
+        }
+        if let Some(uid) = user_id {
+            checks.push((
+                BucketKey::User(addr.platform, uid),
+                self.cfg.user_capacity,
+                self.cfg.user_refill,
+            ));
+        }
+
+        let mut wait = user_id
+            .and_then(|uid| st.cooldowns.get(&(addr.platform, uid, command)))
+            .map(|until| until.saturating_duration_since(now))
+            .unwrap_or_default();
+
+        for (key, capacity, refill) in &checks {
+            let cost = f64::from(cost).min(*capacity);
+            let b = st.buckets.entry(key.clone()).or_insert(Bucket {
+                tokens: *capacity,
+                updated: now,
+                capacity: *capacity,
+                refill: *refill,
+            });
+            b.tokens = (b.tokens + now.duration_since(b.updated).as_secs_f64() * refill)
+                .min(*capacity);
+            b.updated = now;
+            b.capacity = *capacity;
+            b.refill = *refill;
+            if b.tokens < cost {
+                let secs = if *refill > 0.0 {
+                    (cost - b.tokens) / refill
+                } else {
+                    f64::MAX
+                };
+                wait = wait.max(Duration::from_secs_f64(secs.min(86_400.0)));
+            }
+        }
+        if !wait.is_zero() {
+            return Some(wait);
+        }
+
+        for (key, capacity, _) in &checks {
+            if let Some(b) = st.buckets.get_mut(key) {
+                b.tokens -= f64::from(cost).min(*capacity);
+            }
+        }
+        if let (Some(uid), Some(cd)) = (user_id, cooldown) {
+            st.cooldowns.insert((addr.platform, uid, command), now + cd);
+        }
+        None
+    }
+
+    /// “请稍候”提示本身也限频：同一用户在 `notice-interval` 内只提示一次
+    pub fn should_notify(&self, platform: ChatPlatform, user_id: Option<i64>) -> bool {
+        let Some(uid) = user_id else {
+            return false;
+        };
+        let now = Instant::now();
+        let interval = Duration::from_secs(self.cfg.notice_interval);
+        let mut st = self.state.lock().unwrap();
+        self.maybe_purge(&mut st, now);
+        match st.notices.get(&(platform, uid)) {
+            Some(last) if now.duration_since(*last) < interval => false,
+            _ => {
+                st.notices.insert((platform, uid), now);
+                true
+            }
+        }
+    }
+
+    fn maybe_purge(&self, st: &mut State, now: Instant) {
+        st.since_purge += 1;
+        if st.since_purge >= PURGE_EVERY || st.buckets.len() > PURGE_THRESHOLD {
+            self.purge(st, now);
+        }
+    }
+
+    fn purge(&self, st: &mut State, now: Instant) {
+        st.since_purge = 0;
+        st.buckets.retain(|_, b| {
+            b.tokens + now.duration_since(b.updated).as_secs_f64() * b.refill < b.capacity
+        });
+        st.cooldowns.retain(|_, until| *until > now);
+        let interval = Duration::from_secs(self.cfg.notice_interval);
+        st.notices.retain(|_, last| now.duration_since(*last) < interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAT: ChatLimit = ChatLimit {
+        enabled: true,
+        capacity: 100.0,
+        refill: 0.0,
+    };
+
+    fn limiter(user_capacity: f64, user_refill: f64) -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            user_capacity,
+            user_refill,
+            ..RateLimitConfig::default()
+        })
+    }
+
+    fn addr() -> Address {
+        Address::new(ChatPlatform::Onebot, 1i64, true)
+    }
+
+    #[test]
+    fn bucket_drains_to_capacity_then_waits() {
+        let rl = limiter(3.0, 0.5);
+        for _ in 0..3 {
+            assert_eq!(rl.acquire(&addr(), CHAT, Some(7), "ping", 1, None), None);
+        }
+        let wait = rl.acquire(&addr(), CHAT, Some(7), "ping", 1, None).unwrap();
+        assert!(wait > Duration::from_millis(1900) && wait <= Duration::from_secs(2));
+        // 其他用户不受影响
+        assert_eq!(rl.acquire(&addr(), CHAT, Some(8), "ping", 1, None), None);
+    }
+
+    #[test]
+    fn bucket_refills_over_time_up_to_capacity() {
+        let rl = limiter(2.0, 100.0);
+        assert_eq!(rl.acquire(&addr(), CHAT, Some(7), "ping", 2, None), None);
+        assert!(rl.acquire(&addr(), CHAT, Some(7), "ping", 1, None).is_some());
+
+        std::thread::sleep(Duration::from_millis(50));
+        // 50ms 足够回满，但不会超过容量
+        assert_eq!(rl.acquire(&addr(), CHAT, Some(7), "ping", 2, None), None);
+        assert!(rl.acquire(&addr(), CHAT, Some(7), "ping", 1, None).is_some());
+    }
+
+    #[test]
+    fn cost_above_capacity_is_capped_and_rejection_is_free() {
+        let rl = limiter(2.0, 0.0);
+        assert_eq!(rl.acquire(&addr(), CHAT, Some(7), "big", 10, None), None);
+        assert!(rl.acquire(&addr(), CHAT, Some(7), "ping", 1, None).is_some());
+
+        let rl = limiter(2.0, 0.0);
+        let chat = ChatLimit { capacity: 1.0, ..CHAT };
+        assert_eq!(rl.acquire(&addr(), chat, Some(7), "ping", 1, None), None);
+        // 会话桶拒绝时用户桶也不扣
+        assert!(rl.acquire(&addr(), chat, Some(7), "ping", 1, None).is_some());
+        let other = Address::new(ChatPlatform::Onebot, 2i64, true);
+        assert_eq!(rl.acquire(&other, CHAT, Some(7), "ping", 1, None), None);
+    }
+
+    #[test]
+    fn chat_switch_only_skips_the_chat_bucket() {
+        let rl = limiter(1.0, 0.0);
+        let off = ChatLimit { enabled: false, capacity: 0.0, ..CHAT };
+        assert_eq!(rl.acquire(&addr(), off, Some(7), "ping", 1, None), None);
+        assert!(rl.acquire(&addr(), off, Some(7), "ping", 1, None).is_some());
+
+        let rl = RateLimiter::new(RateLimitConfig {
+            enabled: false,
+            ..RateLimitConfig::default()
+        });
+        let cd = Some(Duration::from_secs(30));
+        for _ in 0..10 {
+            assert_eq!(rl.acquire(&addr(), CHAT, Some(7), "ping", 1, cd), None);
+        }
+    }
+
+    #[test]
+    fn cooldown_applies_per_command() {
+        let rl = limiter(10.0, 0.0);
+        let cd = Some(Duration::from_secs(30));
+        assert_eq!(rl.acquire(&addr(), CHAT, Some(7), "github", 1, cd), None);
+        let wait = rl.acquire(&addr(), CHAT, Some(7), "github", 1, cd).unwrap();
+        assert!(wait > Duration::from_secs(29));
+        assert_eq!(rl.acquire(&addr(), CHAT, Some(7), "ping", 1, None), None);
+    }
+
+    #[test]
+    fn expired_entries_are_purged_periodically() {
+        let rl = RateLimiter::new(RateLimitConfig {
+            notice_interval: 0,
+            ..RateLimitConfig::default()
+        });
+        let cd = Some(Duration::ZERO);
+        for uid in 0..PURGE_EVERY as i64 - 1 {
+            rl.acquire(&addr(), CHAT, Some(uid), "ping", 1, cd);
+        }
+        assert!(rl.should_notify(ChatPlatform::Onebot, Some(1)));
+
+        let st = rl.state.lock().unwrap();
+        assert!(st.cooldowns.is_empty());
+        assert!(st.notices.len() <= 1);
+        assert_eq!(st.since_purge, 0);
+    }
+}
@@ -0,0 +1,231 @@
+// src/core/syntax_error.rs
+// 把 brigadier 的解析错误整理成易读的回复：光标位置、此处可接受的输入、拼写建议
+use azalea_brigadier::builder::argument_builder::ArgumentBuilderType;
+use azalea_brigadier::errors::{BuiltInError, CommandSyntaxError};
+use azalea_brigadier::tree::CommandNode;
+
+use crate::core::command_source::CommandSource;
+use crate::core::dispatcher::CommandDispatcher;
+use crate::i18n::{self, tr};
+use crate::model::Segment;
+
+/// 首个词不是任何已注册命令（多半是发给其他 bot 的命令）
+pub fn is_unknown_command(d: &CommandDispatcher<CommandSource>, cmd_line: &str) -> bool {
+    let name = cmd_line.split_whitespace().next().unwrap_or("");
+    !d.root.read().children.contains_key(name)
+}
+
+pub fn report(
+    d: &CommandDispatcher<CommandSource>,
+    source: &CommandSource,
+    cmd_line: &str,
+    e: &CommandSyntaxError,
+    prefix: &str,
+) -> Vec<Segment> {
+    let mut cursor = e.cursor().unwrap_or(0).min(cmd_line.len());
+    while !cmd_line.is_char_boundary(cursor) {
+        cursor -= 1;
+    }
+    let token = cmd_line[cursor..].split_whitespace().next().unwrap_or("");
+    let (expected, literals) = expected_at(d, source, &cmd_line[..cursor]);
+
+    let lang = source.lang();
+    let caret = " ".repeat(prefix.chars().count() + cmd_line[..cursor].chars().count());
+    let reason = describe(e, cursor >= cmd_line.len(), lang);
+    let mut segs = vec![
+        Segment::text(tr!(lang, "syntax.header", reason = reason)),
+        Segment::CodeBlock {
+            lang: None,
+            code: format!("{prefix}{cmd_line}\n{caret}^"),
+        },
+    ];
+
+    if !expected.is_empty() {
+        segs.push(Segment::text(tr!(lang, "syntax.expected")));
+        for (i, x) in expected.iter().enumerate() {
+            if i > 0 {
+                segs.push(Segment::text(tr!(lang, "common.list-separator")));
+            }
+            segs.push(Segment::code(x.clone()));
+        }
+    }
+
+    if let Some(s) = closest(token, &literals) {
+        let fixed = format!(
+            "{prefix}{}{s}{}",
+            &cmd_line[..cursor],
+            &cmd_line[cursor + token.len()..]
+        );
+        let text = tr!(lang, "syntax.suggest");
+        let (before, after) = i18n::split_at(&text, "command");
+        segs.push(Segment::text(before));
+        segs.push(Segment::code(fixed.trim_end().to_string()));
+        segs.push(Segment::text(after));
+    }
+    segs
+}
+
+fn describe(e: &CommandSyntaxError, at_end: bool, lang: &str) -> String {
+    match e.kind() {
+        BuiltInError::DispatcherUnknownCommand if at_end => tr!(lang, "syntax.incomplete"),
+        BuiltInError::DispatcherUnknownCommand => tr!(lang, "syntax.unknown-command"),
+        BuiltInError::DispatcherUnknownArgument => tr!(lang, "syntax.unknown-argument"),
+        BuiltInError::DispatcherExpectedArgumentSeparator => {
+            tr!(lang, "syntax.expected-separator")
+        }
+        BuiltInError::ReaderExpectedInt | BuiltInError::ReaderExpectedLong => {
+            tr!(lang, "syntax.expected-int")
+        }
+        BuiltInError::ReaderInvalidInt { value } | BuiltInError::ReaderInvalidLong { value } => {
+            tr!(lang, "syntax.invalid-int", value = value)
+        }
+        BuiltInError::ReaderExpectedDouble | BuiltInError::ReaderExpectedFloat => {
+            tr!(lang, "syntax.expected-number")
+        }
+        BuiltInError::ReaderInvalidDouble { value } | BuiltInError::ReaderInvalidFloat { value } => {
+            tr!(lang, "syntax.invalid-number", value = value)
+        }
+        BuiltInError::ReaderExpectedBool => tr!(lang, "syntax.expected-bool"),
+        BuiltInError::ReaderInvalidBool { value } => {
+            tr!(lang, "syntax.invalid-bool", value = value)
+        }
+        BuiltInError::IntegerTooSmall { found, min } => {
+            tr!(lang, "syntax.too-small", found = found, min = min)
+        }
+        BuiltInError::IntegerTooBig { found, max } => {
+            tr!(lang, "syntax.too-big", found = found, max = max)
+        }
+        BuiltInError::LongTooSmall { found, min } => {
+            tr!(lang, "syntax.too-small", found = found, min = min)
+        }
+        BuiltInError::LongTooBig { found, max } => {
+            tr!(lang, "syntax.too-big", found = found, max = max)
+        }
+        BuiltInError::DoubleTooSmall { found, min } => {
+            tr!(lang, "syntax.too-small", found = found, min = min)
+        }
+        BuiltInError::DoubleTooBig { found, max } => {
+            tr!(lang, "syntax.too-big", found = found, max = max)
+        }
+        BuiltInError::ReaderExpectedStartOfQuote | BuiltInError::ReaderExpectedEndOfQuote => {
+            tr!(lang, "syntax.quote")
+        }
+        _ => e.raw_message().clone(),
+    }
+}
+
+/// 沿已成功解析的部分走到出错的节点，列出当前用户有权使用的子节点；同时返回可用于拼写建议的字面量
+fn expected_at(
+    d: &CommandDispatcher<CommandSource>,
+    source: &CommandSource,
+    parsed: &str,
+) -> (Vec<String>, Vec<String>) {
+    let mut node = d.root.clone();
+    for word in parsed.split_whitespace() {
+        let next = {
+            let n = node.read();
+            n.children.get(word).cloned().or_else(|| {
+                n.children
+                    .values()
+                    .find(|c| matches!(c.read().value, ArgumentBuilderType::Argument(_)))
+                    .cloned()
+            })
+        };
+        match next {
+            // 别名是重定向节点，子节点在目标上
+            Some(c) => node = c.read().redirect.clone().unwrap_or_else(|| c.clone()),
+            None => break,
+        }
+    }
+
+    let n = node.read();
+    let mut expected = vec![];
+    let mut literals = vec![];
+    for c in n.children.values() {
+        let c: &CommandNode<CommandSource> = &c.read();
+        if !c.can_use(source) {
+            continue;
+        }
+        match &c.value {
+            ArgumentBuilderType::Literal(l) => {
+                expected.push(l.value.clone());
+                literals.push(l.value.clone());
+            }
+            ArgumentBuilderType::Argument(a) => expected.push(format!("<{}>", a.name)),
+        }
+    }
+    (expected, literals)
+}
+
+/// 编辑距离不超过词长三分之一（至少 1、至多 3）时给出建议
+fn closest(token: &str, candidates: &[String]) -> Option<String> {
+    if token.is_empty() {
+        return None;
+    }
+    let token = token.to_lowercase();
+    let max = (token.chars().count() / 3).clamp(1, 3);
+    candidates
+        .iter()
+        .map(|c| (edit_distance(&token, c), c))
+        .filter(|(dist, _)| *dist <= max)
+        .min_by_key(|(dist, _)| *dist)
+        .map(|(_, c)| c.clone())
+}
+
+/// 编辑距离，相邻字符互换算一次（`pign` → `ping`）
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn edit_distance_counts_transpositions_once() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("ping", ""), 4);
+        assert_eq!(edit_distance("ping", "ping"), 0);
+        assert_eq!(edit_distance("pign", "ping"), 1);
+        assert_eq!(edit_distance("pin", "ping"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("设置", "设定"), 1);
+    }
+
+    #[test]
+    fn closest_respects_length_scaled_threshold() {
+        let cmds = names(&["help", "ping", "prefix", "settings", "github"]);
+        assert_eq!(closest("pign", &cmds).as_deref(), Some("ping"));
+        assert_eq!(closest("HLEP", &cmds).as_deref(), Some("help"));
+        assert_eq!(closest("setings", &cmds).as_deref(), Some("settings"));
+        assert_eq!(closest("gthb", &cmds), None);
+        assert_eq!(closest("xyz", &cmds), None);
+        assert_eq!(closest("", &cmds), None);
+        // 距离相同时取先出现的候选
+        assert_eq!(closest("pxng", &names(&["pang", "ping"])).as_deref(), Some("pang"));
+    }
+}
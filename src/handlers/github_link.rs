@@ -0,0 +1,192 @@
+// src/handlers/github_link.rs
+// 展开消息中的 GitHub 仓库 / Issue / PR 链接
+use std::sync::{Arc, LazyLock};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use regex::Regex;
+use tracing::debug;
+
+use crate::commands::github::GitHubApi;
+use crate::core::message_handler::{Matcher, MessageHandler};
+use crate::i18n::tr;
+use crate::model::{MessageIn, MessageOut, Segment};
+
+/// 每条消息最多展开的链接数
+const MAX_LINKS: usize = 3;
+
+/// github.com 下不是用户名的一级路径
+const RESERVED: &[&str] = &[
+    "about", "apps", "collections", "enterprise", "explore", "features", "issues", "login",
+    "marketplace", "new", "notifications", "orgs", "pricing", "pulls", "search", "settings",
+    "sponsors", "topics", "trending",
+];
+
+/// 主机名前只能是开头、协议的 `/` 或分隔符，`gist.github.com`、`api.github.com` 不算
+static LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:^|[^\w.-])(?:www\.)?github\.com/([a-z0-9][a-z0-9-]*)/([a-z0-9._-]+)(?:/(issues|pull)/(\d+))?",
+    )
+    .unwrap()
+});
+
+enum Link {
+    Repo(String, String),
+    Issue(String, String, u64),
+}
+
+pub struct GitHubLinkHandler {
+    api: Arc<GitHubApi>,
+    matcher: Matcher,
+}
+
+impl GitHubLinkHandler {
+    pub fn new(api: Arc<GitHubApi>) -> Self {
+        Self {
+            api,
+            matcher: Matcher::Regex(LINK.clone()),
+        }
+    }
+
+    async fn describe(&self, link: &Link, lang: &str) -> Result<Vec<Segment>> {
+        Ok(match link {
+            Link::Repo(owner, repo) => {
+                let r = self.api.get_repo(owner, repo).await?;
+                let mut segs = vec![
+                    Segment::link(r.full_name.clone(), r.html_url.clone()),
+                    Segment::text(tr!(
+                        lang,
+                        "github.link-repo",
+                        language = r
+                            .language
+                            .clone()
+                            .unwrap_or_else(|| tr!(lang, "github.unknown-language")),
+                        stars = r.stargazers_count,
+                        forks = r.forks_count,
+                    )),
+                ];
+                if let Some(d) = r.description.as_deref().filter(|d| !d.trim().is_empty()) {
+                    segs.push(Segment::text(format!("\n{}", d.trim())));
+                }
+                segs
+            }
+            Link::Issue(owner, repo, number) => {
+                let i = self.api.get_issue(owner, repo, *number).await?;
+                let kind = if i.pull_request.is_some() { "PR" } else { "Issue" };
+                vec![
+                    Segment::link(
+                        format!("{owner}/{repo} {kind} #{}", i.number),
+                        i.html_url.clone(),
+                    ),
+                    Segment::text(tr!(
+                        lang,
+                        "github.link-issue",
+                        title = i.title,
+                        state = i.state,
+                        author = i.user.login,
+                        comments = i.comments,
+                    )),
+                ]
+            }
+        })
+    }
+}
+
+fn links_of(text: &str) -> Vec<Link> {
+    let mut out: Vec<Link> = vec![];
+    for c in LINK.captures_iter(text) {
+        let owner = c[1].to_string();
+        // 句末的标点和 .git 后缀不属于仓库名
+        let repo = c[2].trim_end_matches('.').trim_end_matches(".git").to_string();
+        if repo.is_empty() || RESERVED.contains(&owner.to_lowercase().as_str()) {
+            continue;
+        }
+
+        let link = match c.get(4).and_then(|n| n.as_str().parse().ok()) {
+            Some(n) => Link::Issue(owner, repo, n),
+            None => Link::Repo(owner, repo),
+        };
+        let dup = out.iter().any(|l| match (l, &link) {
+            (Link::Repo(a, b), Link::Repo(c, d)) => a == c && b == d,
+            (Link::Issue(a, b, n), Link::Issue(c, d, m)) => a == c && b == d && n == m,
+            _ => false,
+        });
+        if !dup {
+            out.push(link);
+        }
+        if out.len() == MAX_LINKS {
+            break;
+        }
+    }
+    out
+}
+
+#[async_trait]
+impl MessageHandler for GitHubLinkHandler {
+    fn name(&self) -> &'static str {
+        "github-link"
+    }
+
+    fn matcher(&self) -> &Matcher {
+        &self.matcher
+    }
+
+    fn cost(&self) -> Option<u32> {
+        Some(2)
+    }
+
+    /// 请求失败的链接直接跳过，不向群里报错
+    async fn handle(&self, input: &MessageIn, lang: &str) -> Result<Vec<MessageOut>> {
+        let mut segs: Vec<Segment> = vec![];
+        for link in links_of(&input.text) {
+            match self.describe(&link, lang).await {
+                Ok(s) => {
+                    if !segs.is_empty() {
+                        segs.push(Segment::text("\n\n"));
+                    }
+                    segs.extend(s);
+                }
+                Err(e) => debug!("github unfurl skipped: {e:?}"),
+            }
+        }
+
+        if segs.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![MessageOut::new(input.addr.clone(), segs)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(text: &str) -> Vec<String> {
+        links_of(text)
+            .into_iter()
+            .map(|l| match l {
+                Link::Repo(o, r) => format!("{o}/{r}"),
+                Link::Issue(o, r, n) => format!("{o}/{r}#{n}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn only_github_com_links_are_unfurled() {
+        assert_eq!(
+            found("see https://github.com/rust-lang/rust/issues/1 and github.com/serde-rs/serde.git."),
+            ["rust-lang/rust#1", "serde-rs/serde"]
+        );
+        assert_eq!(found("(https://www.github.com/tokio-rs/tokio/pull/42)"), ["tokio-rs/tokio#42"]);
+        assert!(found("https://gist.github.com/someone/abcdef").is_empty());
+        assert!(found("https://api.github.com/repos/a/b").is_empty());
+        assert!(found("https://notgithub.com/a/b").is_empty());
+        assert!(found("https://github.com/settings/profile").is_empty());
+    }
+
+    #[test]
+    fn duplicates_are_dropped_and_count_is_capped() {
+        let text = "github.com/a/x github.com/a/x github.com/a/y github.com/a/z github.com/a/w";
+        assert_eq!(found(text), ["a/x", "a/y", "a/z"]);
+    }
+}
@@ -0,0 +1,3 @@
+// src/handlers/mod.rs
+// 内置的普通消息处理器
+pub mod github_link;
@@ -0,0 +1,178 @@
+// src/i18n.rs
+// 面向用户的文本：resources/lang/<locale>.toml 消息目录 + `{name}` 参数插值
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::LazyLock;
+
+/// 目录中找不到某条文本时回退到的语言
+pub const FALLBACK: &str = "zh-cn";
+
+/// 编译期嵌入的消息目录；新增语言只需在此登记并添加同名文件
+const CATALOGS: &[(&str, &str)] = &[
+    ("zh-cn", include_str!("../resources/lang/zh-cn.toml")),
+    ("en-us", include_str!("../resources/lang/en-us.toml")),
+];
+
+/// 语言 -> 展平后的 `section.key` -> 文本
+static BUNDLES: LazyLock<HashMap<&'static str, HashMap<String, String>>> = LazyLock::new(|| {
+    CATALOGS
+        .iter()
+        .map(|(locale, src)| {
+            let table: toml::Table = toml::from_str(src)
+                .unwrap_or_else(|e| panic!("invalid message catalog {locale}: {e}"));
+            let mut out = HashMap::new();
+            flatten("", &table, &mut out);
+            (*locale, out)
+        })
+        .collect()
+});
+
+fn flatten(prefix: &str, table: &toml::Table, out: &mut HashMap<String, String>) {
+    for (k, v) in table {
+        let key = if prefix.is_empty() {
+            k.clone()
+        } else {
+            format!("{prefix}.{k}")
+        };
+        match v {
+            toml::Value::String(s) => {
+                out.insert(key, s.clone());
+            }
+            toml::Value::Table(t) => flatten(&key, t, out),
+            _ => {}
+        }
+    }
+}
+
+/// 已内置的全部语言
+pub fn locales() -> impl Iterator<Item = &'static str> {
+    CATALOGS.iter().map(|(locale, _)| *locale)
+}
+
+/// 把 `en`、`en_GB`、`zh-CN` 之类的语言标签对应到内置语言：
+/// 先精确匹配，再按主语言匹配第一个同语种的目录
+pub fn supported(tag: &str) -> Option<&'static str> {
+    let tag = tag.trim().to_lowercase().replace('_', "-");
+    if tag.is_empty() {
+        return None;
+    }
+    let primary = tag.split('-').next().unwrap_or("");
+    locales()
+        .find(|l| *l == tag)
+        .or_else(|| locales().find(|l| l.split('-').next() == Some(primary)))
+}
+
+/// 不支持的语言按 `FALLBACK` 处理
+pub fn resolve(tag: &str) -> &'static str {
+    supported(tag).unwrap_or(FALLBACK)
+}
+
+/// 查找文本并替换 `{name}` 占位符；未提供的占位符原样保留，
+/// 找不到的键依次回退到 `FALLBACK` 与键名本身
+pub fn text(lang: &str, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let template = [resolve(lang), FALLBACK]
+        .iter()
+        .find_map(|l| BUNDLES.get(l).and_then(|b| b.get(key)))
+        .map_or(key, String::as_str);
+    interpolate(template, args)
+}
+
+fn interpolate(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let arg = tail.find('}').and_then(|end| {
+            let name = &tail[1..end];
+            args.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| (end, v.to_string()))
+        });
+        match arg {
+            Some((end, v)) => {
+                out.push_str(&v);
+                rest = &tail[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 以 `{name}` 为界把文本拆成前后两段，供中间插入代码等非文本片段
+pub fn split_at<'a>(text: &'a str, name: &str) -> (&'a str, &'a str) {
+    text.split_once(&format!("{{{name}}}")).unwrap_or((text, ""))
+}
+
+/// `tr!(lang, "help.title")`、`tr!(lang, "common.save-failed", error = e)`
+macro_rules! tr {
+    ($lang:expr, $key:expr) => {
+        $crate::i18n::text($lang, $key, &[])
+    };
+    ($lang:expr, $key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::text(
+            $lang,
+            $key,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+],
+        )
+    };
+}
+pub(crate) use tr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supported_matches_exact_then_primary_subtag() {
+        assert_eq!(supported("zh-CN"), Some("zh-cn"));
+        assert_eq!(supported(" en_US "), Some("en-us"));
+        assert_eq!(supported("en"), Some("en-us"));
+        assert_eq!(supported("en-GB"), Some("en-us"));
+        assert_eq!(supported("zh-TW"), Some("zh-cn"));
+        assert_eq!(supported("fr"), None);
+        assert_eq!(supported(""), None);
+        assert_eq!(resolve("fr-FR"), FALLBACK);
+    }
+
+    #[test]
+    fn interpolate_replaces_known_placeholders_only() {
+        let args: &[(&str, &dyn Display)] = &[("name", &"ping"), ("n", &3)];
+        assert_eq!(interpolate("{name} x{n}", args), "ping x3");
+        assert_eq!(interpolate("{name}{name}", args), "pingping");
+        assert_eq!(interpolate("{other} {name}", args), "{other} ping");
+        assert_eq!(interpolate("{ {name} }", args), "{ ping }");
+        assert_eq!(interpolate("{name", args), "{name");
+        assert_eq!(interpolate("命令 {name}。", args), "命令 ping。");
+    }
+
+    #[test]
+    fn text_falls_back_to_default_catalog_then_key() {
+        assert_eq!(tr!("en-us", "ping.usage"), "ping");
+        assert_eq!(
+            tr!("en", "common.unknown-command", name = "x"),
+            "Unknown command: x"
+        );
+        assert_eq!(tr!("fr", "common.unknown-command", name = "x"), "未知命令：x");
+        assert_eq!(tr!("en-us", "no.such-key"), "no.such-key");
+        assert_eq!(split_at("a {code} b", "code"), ("a ", " b"));
+        assert_eq!(split_at("plain", "code"), ("plain", ""));
+    }
+
+    /// 每种语言都应覆盖默认目录中的全部键
+    #[test]
+    fn catalogs_have_the_same_keys() {
+        let base = &BUNDLES[FALLBACK];
+        for locale in locales() {
+            let mut missing: Vec<&String> =
+                base.keys().filter(|k| !BUNDLES[locale].contains_key(*k)).collect();
+            missing.sort();
+            assert!(missing.is_empty(), "{locale} is missing {missing:?}");
+        }
+    }
+}
@@ -4,17 +4,25 @@ mod model;
 
 mod commands;
 mod core;
+mod handlers;
+mod i18n;
+mod middlewares;
 mod platform;
+mod storage;
 
 use anyhow::{Context, Result};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
-use crate::core::{CommandRegistry, MessageDispatcher, MessageSenderHub, PipelineProcessor};
-use crate::lifecycle::{BaseCloseable, PlatformGuard};
-use crate::platform::{discord::DiscordReceiver, telegram::TelegramReceiver};
+use crate::core::chat_settings::ChatSettingsStore;
+use crate::core::{
+    CommandRegistry, MessageDispatcher, MessageSenderHub, Paginator, PipelineProcessor,
+};
+use crate::lifecycle::PlatformGuard;
+use crate::platform::{PlatformHealth, PlatformRegistry};
+use crate::storage::Storage;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -30,26 +38,43 @@ async fn main() -> Result<()> {
     let t0 = Instant::now();
     let props = Arc::new(config::load_or_init()?);
     info!(
-        "config loaded in {:?} (prefix='{}', telegram_enabled={}, discord_enabled={})",
+        "config loaded in {:?} (prefixes={:?}, telegram_enabled={}, discord_enabled={}, onebot_enabled={})",
         t0.elapsed(),
-        props.prefix,
+        props.prefixes,
         props.telegram.enabled,
         props.discord.enabled,
+        props.onebot.enabled,
+    );
+
+    // ---- storage ----
+    let t0 = Instant::now();
+    let storage = Storage::open(&props.storage).context("open storage")?;
+    storage.migrate().await.context("migrate storage")?;
+    info!(
+        "storage ready in {:?} (backend={})",
+        t0.elapsed(),
+        storage.backend_name()
+    );
+    let settings = Arc::new(
+        ChatSettingsStore::load(props.clone(), &storage)
+            .await
+            .context("load chat settings")?,
     );
 
     // ---- core: hub / registry / pipeline / dispatcher ----
     let t0 = Instant::now();
-    let hub = MessageSenderHub::new();
+    let paginator = Arc::new(Paginator::new(Duration::from_secs(props.page_ttl)));
+    let hub = MessageSenderHub::new(props.long_text_file_threshold, paginator.clone());
     debug!("MessageSenderHub created");
 
-    let registry = CommandRegistry::build(props.clone());
+    let registry = CommandRegistry::build(props.clone(), settings)?;
     info!(
         "CommandRegistry built in {:?} (commands: {})",
         t0.elapsed(),
         registry.list_commands()
     );
 
-    let pipeline = PipelineProcessor::new(props.clone(), registry.clone());
+    let pipeline = PipelineProcessor::new(props.clone(), registry.clone(), hub.clone());
     debug!("PipelineProcessor created");
 
     let (in_tx, in_rx) = mpsc::unbounded_channel();
@@ -58,60 +83,21 @@ async fn main() -> Result<()> {
     let dispatcher = Arc::new(MessageDispatcher::new(
         pipeline,
         hub.clone(),
-        props.prefix.clone(),
+        paginator,
     ));
     info!("MessageDispatcher created");
 
     // ---- platforms ----
-    let mut closeable = BaseCloseable::new();
-    let mut enabled_any = false;
-
-    if props.telegram.enabled {
-        let t0 = Instant::now();
-        enabled_any = true;
-
-        info!("starting TelegramReceiver...");
-        let tg = TelegramReceiver::new(props.telegram.bot_token.clone());
-
-        tg.bind(in_tx.clone()).await;
-        debug!("TelegramReceiver bind done");
-
-        tg.start().await.context("TelegramReceiver.start failed")?;
-        info!("TelegramReceiver started in {:?}", t0.elapsed());
-
-        hub.register(crate::model::ChatPlatform::Telegram, tg.sender().await?);
-        debug!("Telegram sender registered into hub");
-
-        closeable.add(Box::new(tg));
-        info!("Telegram ready");
-    } else {
-        info!("Telegram disabled by config");
+    let platforms = Arc::new(PlatformRegistry::build(&props).context("build platforms")?);
+    PlatformGuard::ensure(&platforms).context("no platform enabled")?;
+    info!("platform guard ok (platforms: {})", platforms.list_platforms());
+
+    platforms.start_all(in_tx.clone(), &hub).await?;
+    platforms.sync_commands(&registry).await;
+    for (name, health) in platforms.health() {
+        info!("{name}: {health:?}");
     }
-
-    if props.discord.enabled {
-        let t0 = Instant::now();
-        enabled_any = true;
-
-        info!("starting DiscordReceiver...");
-        let dc = DiscordReceiver::new(props.discord.token.clone(), props.proxy.clone());
-
-        dc.bind(in_tx.clone()).await;
-        debug!("DiscordReceiver bind done");
-
-        dc.start().await.context("DiscordReceiver.start failed")?;
-        info!("DiscordReceiver started in {:?}", t0.elapsed());
-
-        hub.register(crate::model::ChatPlatform::Discord, dc.sender().await?);
-        debug!("Discord sender registered into hub");
-
-        closeable.add(Box::new(dc));
-        info!("Discord ready");
-    } else {
-        info!("Discord disabled by config");
-    }
-
-    PlatformGuard::ensure(enabled_any).context("no platform enabled")?;
-    info!("platform guard ok (enabled_any={})", enabled_any);
+    info!("platforms ready");
 
     // ---- dispatcher task ----
     info!("spawning dispatcher loop...");
@@ -124,14 +110,33 @@ async fn main() -> Result<()> {
         })
     };
 
+    // ---- platform health watchdog ----
+    let watchdog_task = {
+        let platforms = platforms.clone();
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(Duration::from_secs(60));
+            tick.tick().await;
+            loop {
+                tick.tick().await;
+                for (name, health) in platforms.health() {
+                    if health != PlatformHealth::Running {
+                        warn!("platform {name} unhealthy: {health:?}");
+                    }
+                }
+            }
+        })
+    };
+
     info!("boot completed in {:?}", boot_t0.elapsed());
 
     // ---- shutdown ----
     tokio::signal::ctrl_c().await?;
     warn!("Ctrl+C received, shutting down...");
 
-    info!("closing platforms (BaseCloseable)...");
-    closeable.close();
+    watchdog_task.abort();
+
+    info!("shutting down platforms...");
+    platforms.shutdown_all().await;
     info!("platforms closed");
 
     info!("stopping dispatcher...");
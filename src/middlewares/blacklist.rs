@@ -0,0 +1,45 @@
+// src/middlewares/blacklist.rs
+// 忽略黑名单中的用户与会话
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tracing::debug;
+
+use crate::config::AppProperties;
+use crate::core::middleware::{Flow, Middleware};
+use crate::model::MessageIn;
+
+/// 会话键与 `[reply.chats]` 相同，形如 `onebot:group:123456` 或 `telegram:123456`（小写）
+pub struct BlacklistMiddleware {
+    users: HashSet<String>,
+    chats: HashSet<String>,
+}
+
+pub fn from_props(props: &AppProperties) -> Arc<dyn Middleware> {
+    let norm = |v: &[String]| v.iter().map(|s| s.trim().to_lowercase()).collect();
+    Arc::new(BlacklistMiddleware {
+        users: norm(&props.pipeline.blacklist.users),
+        chats: norm(&props.pipeline.blacklist.chats),
+    })
+}
+
+#[async_trait]
+impl Middleware for BlacklistMiddleware {
+    fn name(&self) -> &'static str {
+        "blacklist"
+    }
+
+    async fn before(&self, input: &mut MessageIn) -> Flow {
+        let chat = input.addr.key();
+        let user = input
+            .user_id
+            .map(|u| format!("{:?}:{u}", input.addr.platform).to_lowercase());
+
+        if self.chats.contains(&chat.config_name()) || user.is_some_and(|u| self.users.contains(&u)) {
+            debug!("blacklisted: chat={chat} user={:?}", input.user_id);
+            return Flow::Stop(vec![]);
+        }
+        Flow::Continue
+    }
+}
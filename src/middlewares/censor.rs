@@ -0,0 +1,67 @@
+// src/middlewares/censor.rs
+// 把输出文本中的敏感词替换为等长的遮挡字符
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::config::AppProperties;
+use crate::core::middleware::Middleware;
+use crate::model::{MessageIn, MessageOut, Segment};
+
+pub struct CensorMiddleware {
+    words: Vec<String>,
+    mask: char,
+}
+
+pub fn from_props(props: &AppProperties) -> Arc<dyn Middleware> {
+    let cfg = &props.pipeline.censor;
+    Arc::new(CensorMiddleware {
+        words: cfg
+            .words
+            .iter()
+            .map(|w| w.trim().to_string())
+            .filter(|w| !w.is_empty())
+            .collect(),
+        mask: cfg.mask.chars().next().unwrap_or('*'),
+    })
+}
+
+impl CensorMiddleware {
+    fn censor(&self, s: &mut String) {
+        for w in &self.words {
+            if s.contains(w.as_str()) {
+                *s = s.replace(w.as_str(), &self.mask.to_string().repeat(w.chars().count()));
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for CensorMiddleware {
+    fn name(&self) -> &'static str {
+        "censor"
+    }
+
+    async fn after(&self, _input: &MessageIn, outs: &mut Vec<MessageOut>) {
+        if self.words.is_empty() {
+            return;
+        }
+        let pages = outs.iter_mut().flat_map(|o| {
+            std::iter::once(&mut o.segments).chain(o.pages.iter_mut())
+        });
+        for segs in pages {
+            for seg in segs.iter_mut() {
+                match seg {
+                    Segment::Text(t)
+                    | Segment::Bold(t)
+                    | Segment::Italic(t)
+                    | Segment::Code(t)
+                    | Segment::Quote(t) => self.censor(t),
+                    Segment::CodeBlock { code, .. } => self.censor(code),
+                    Segment::Link { text, .. } => self.censor(text),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+// src/middlewares/keyword_reply.rs
+// 普通消息包含关键词时自动回复；命令与 bot 发出的消息不处理
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::config::AppProperties;
+use crate::core::middleware::{Flow, Middleware};
+use crate::core::prefix::Prefixes;
+use crate::model::{MessageIn, MessageOut};
+
+pub struct KeywordReplyMiddleware {
+    prefixes: Arc<Prefixes>,
+    /// (小写关键词, 回复)，按关键词从长到短排列，优先匹配更具体的
+    rules: Vec<(String, String)>,
+}
+
+/// 需要按会话前缀识别命令，因此不走 `FACTORIES`，由 `build_chain` 单独传入前缀
+pub fn from_props(props: &AppProperties, prefixes: Arc<Prefixes>) -> Arc<dyn Middleware> {
+    let mut rules: Vec<(String, String)> = props
+        .pipeline
+        .keyword_reply
+        .iter()
+        .filter(|(k, _)| !k.trim().is_empty())
+        .map(|(k, v)| (k.trim().to_lowercase(), v.clone()))
+        .collect();
+    rules.sort_by(|a, b| b.0.chars().count().cmp(&a.0.chars().count()).then(a.0.cmp(&b.0)));
+
+    Arc::new(KeywordReplyMiddleware {
+        prefixes,
+        rules,
+    })
+}
+
+#[async_trait]
+impl Middleware for KeywordReplyMiddleware {
+    fn name(&self) -> &'static str {
+        "keyword-reply"
+    }
+
+    fn wants_plain_text(&self) -> bool {
+        !self.rules.is_empty()
+    }
+
+    async fn before(&self, input: &mut MessageIn) -> Flow {
+        let text = input.text.trim();
+        if text.is_empty() || input.sender.is_bot || self.prefixes.is_command(input) {
+            return Flow::Continue;
+        }
+
+        let lower = text.to_lowercase();
+        match self.rules.iter().find(|(k, _)| lower.contains(k.as_str())) {
+            Some((_, reply)) => Flow::Stop(vec![MessageOut::text(input.addr.clone(), reply)]),
+            // 未命中的普通消息继续交给普通消息处理器
+            None => Flow::Continue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::command_processor::TestBot;
+    use crate::model::{Address, ChatPlatform};
+
+    async fn middleware() -> Arc<dyn Middleware> {
+        let mut props = AppProperties::default();
+        props.pipeline.keyword_reply.insert("早安".to_string(), "早上好！".to_string());
+        props.pipeline.keyword_reply.insert("Hello".to_string(), "hi".to_string());
+        let bot = TestBot::new(props).await;
+        from_props(&bot.props, bot.registry.prefixes().clone())
+    }
+
+    async fn run(m: &Arc<dyn Middleware>, text: &str, is_bot: bool) -> Option<String> {
+        let addr = Address::new(ChatPlatform::Onebot, 1i64, true);
+        let mut input = MessageIn::new(addr, 7, text.to_string());
+        input.sender.is_bot = is_bot;
+        match m.before(&mut input).await {
+            Flow::Continue => None,
+            Flow::Stop(outs) => Some(outs.iter().map(MessageOut::plain_text).collect()),
+        }
+    }
+
+    /// 未命中关键词的普通消息必须继续往下传，不能被吞掉
+    #[tokio::test]
+    async fn only_matching_plain_text_stops() {
+        let m = middleware().await;
+        assert_eq!(run(&m, "大家早安", false).await.as_deref(), Some("早上好！"));
+        assert_eq!(run(&m, "HELLO there", false).await.as_deref(), Some("hi"));
+        assert_eq!(run(&m, "随便聊聊", false).await, None);
+        assert_eq!(run(&m, "/help 早安", false).await, None);
+        assert_eq!(run(&m, "早安", true).await, None);
+    }
+}
@@ -0,0 +1,36 @@
+// src/middlewares/logging.rs
+// 记录每条消息经过管线后的输出摘要
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tracing::debug;
+
+use crate::config::AppProperties;
+use crate::core::middleware::Middleware;
+use crate::model::{MessageIn, MessageOut};
+
+pub struct LoggingMiddleware;
+
+pub fn from_props(_props: &AppProperties) -> Arc<dyn Middleware> {
+    Arc::new(LoggingMiddleware)
+}
+
+#[async_trait]
+impl Middleware for LoggingMiddleware {
+    fn name(&self) -> &'static str {
+        "logging"
+    }
+
+    async fn after(&self, input: &MessageIn, outs: &mut Vec<MessageOut>) {
+        for o in outs.iter() {
+            debug!(
+                "OUT -> [{:?}] chat={} in_reply_to={:?} text=\"{}\" attachments={}",
+                o.addr.platform,
+                o.addr.chat_id,
+                input.message_id,
+                o.plain_text().chars().take(200).collect::<String>(),
+                o.attachments().count()
+            );
+        }
+    }
+}
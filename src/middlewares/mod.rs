@@ -0,0 +1,54 @@
+// src/middlewares/mod.rs
+// 内置中间件，按 `[pipeline] middlewares` 中的名字和顺序组装
+pub mod blacklist;
+pub mod censor;
+pub mod keyword_reply;
+pub mod logging;
+pub mod rate_limit;
+
+use std::sync::Arc;
+
+use tracing::{info, warn};
+
+use crate::config::AppProperties;
+use crate::core::command_registry::CommandRegistry;
+use crate::core::middleware::Middleware;
+
+type MiddlewareFactory = fn(&AppProperties) -> Arc<dyn Middleware>;
+
+const FACTORIES: &[(&str, MiddlewareFactory)] = &[
+    ("blacklist", blacklist::from_props),
+    ("logging", logging::from_props),
+    ("censor", censor::from_props),
+];
+
+/// 未知的名字只记录警告并跳过
+pub fn build_chain(
+    props: &Arc<AppProperties>,
+    registry: &Arc<CommandRegistry>,
+) -> Vec<Arc<dyn Middleware>> {
+    let chain: Vec<Arc<dyn Middleware>> = props
+        .pipeline
+        .middlewares
+        .iter()
+        .filter_map(|name| {
+            let found = match name.as_str() {
+                "keyword-reply" => {
+                    Some(keyword_reply::from_props(props, registry.prefixes().clone()))
+                }
+                "rate-limit" => Some(rate_limit::from_props(props, registry.clone())),
+                _ => FACTORIES.iter().find(|(n, _)| n == name).map(|(_, f)| f(props)),
+            };
+            if found.is_none() {
+                warn!("unknown middleware `{name}`, skipped");
+            }
+            found
+        })
+        .collect();
+
+    if !chain.is_empty() {
+        let names: Vec<&str> = chain.iter().map(|m| m.name()).collect();
+        info!("pipeline middlewares: {}", names.join(" -> "));
+    }
+    chain
+}
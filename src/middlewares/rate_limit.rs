@@ -0,0 +1,167 @@
+// src/middlewares/rate_limit.rs
+// 命令与普通消息处理器共用的令牌桶限流；bot 所有者不受限制
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::config::AppProperties;
+use crate::core::command_registry::CommandRegistry;
+use crate::core::middleware::{Flow, Middleware};
+use crate::core::rate_limiter::RateLimiter;
+use crate::i18n::tr;
+use crate::model::{MessageIn, MessageOut};
+
+pub struct RateLimitMiddleware {
+    props: Arc<AppProperties>,
+    registry: Arc<CommandRegistry>,
+    limiter: RateLimiter,
+}
+
+/// 需要命令表和会话设置来计算消耗，因此不走 `FACTORIES`，由 `build_chain` 单独传入注册表
+pub fn from_props(
+    props: &Arc<AppProperties>,
+    registry: Arc<CommandRegistry>,
+) -> Arc<dyn Middleware> {
+    Arc::new(RateLimitMiddleware {
+        limiter: RateLimiter::new(props.rate_limit.clone()),
+        props: props.clone(),
+        registry,
+    })
+}
+
+#[async_trait]
+impl Middleware for RateLimitMiddleware {
+    fn name(&self) -> &'static str {
+        "rate-limit"
+    }
+
+    async fn before(&self, input: &mut MessageIn) -> Flow {
+        let platform = input.addr.platform;
+        if input
+            .user_id
+            .is_some_and(|u| self.props.owners_of(platform).contains(&u))
+        {
+            return Flow::Continue;
+        }
+
+        let settings = self.registry.settings();
+        let limit = settings.get(&input.addr).rate_limit;
+
+        let Some((_, cmd_line)) = self.registry.prefixes().parse(input) else {
+            // 普通消息是被动触发：只为第一个匹配的处理器扣令牌，被限流时静默丢弃
+            if input.sender.is_bot {
+                return Flow::Continue;
+            }
+            let Some(h) = self
+                .registry
+                .handlers()
+                .iter()
+                .find(|h| h.matcher().is_match(input))
+            else {
+                return Flow::Continue;
+            };
+            let cost = h.cost().unwrap_or(self.limiter.default_cost());
+            return match self.limiter.acquire(
+                &input.addr,
+                limit,
+                input.user_id,
+                h.name(),
+                cost,
+                None,
+            ) {
+                Some(_) => Flow::Stop(vec![]),
+                None => Flow::Continue,
+            };
+        };
+
+        // 未知命令和已关闭的命令交给命令处理器提示，不扣令牌
+        let Some(c) = cmd_line
+            .split_whitespace()
+            .next()
+            .and_then(|name| self.registry.find(name))
+            .filter(|c| self.registry.is_enabled(&input.addr, c.as_ref()))
+        else {
+            return Flow::Continue;
+        };
+        let cost = c.cost().unwrap_or(self.limiter.default_cost());
+        let Some(wait) = self.limiter.acquire(
+            &input.addr,
+            limit,
+            input.user_id,
+            c.name(),
+            cost,
+            c.cooldown(),
+        ) else {
+            return Flow::Continue;
+        };
+
+        if !self.limiter.should_notify(platform, input.user_id) {
+            return Flow::Stop(vec![]);
+        }
+        let lang = settings.language_for(input);
+        Flow::Stop(vec![MessageOut::text(
+            input.addr.clone(),
+            tr!(
+                lang,
+                "processor.rate-limited",
+                seconds = wait.as_secs_f64().ceil() as u64
+            ),
+        )])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::command_processor::TestBot;
+    use crate::model::{Address, ChatPlatform};
+
+    async fn middleware(props: AppProperties) -> Arc<dyn Middleware> {
+        let bot = TestBot::new(props).await;
+        from_props(&bot.props, bot.registry)
+    }
+
+    fn limited(flow: Flow) -> Option<usize> {
+        match flow {
+            Flow::Stop(outs) => Some(outs.len()),
+            Flow::Continue => None,
+        }
+    }
+
+    /// 用户桶耗尽后第一次提示，之后在提示间隔内静默丢弃；所有者不受限
+    #[tokio::test]
+    async fn limits_commands_per_user_except_owners() {
+        let mut props = AppProperties::default();
+        props.rate_limit.user_capacity = 1.0;
+        props.rate_limit.user_refill = 0.0;
+        props.onebot.owners = vec![7];
+        let m = middleware(props).await;
+
+        let dm = Address::new(ChatPlatform::Onebot, 42i64, false);
+        let mut input = MessageIn::new(dm.clone(), 42i64, "/ping".to_string());
+        assert_eq!(limited(m.before(&mut input).await), None);
+        assert_eq!(limited(m.before(&mut input).await), Some(1));
+        assert_eq!(limited(m.before(&mut input).await), Some(0));
+
+        let mut owner = MessageIn::new(dm, 7i64, "/ping".to_string());
+        for _ in 0..3 {
+            assert_eq!(limited(m.before(&mut owner).await), None);
+        }
+    }
+
+    /// 未知命令不扣令牌，留给命令处理器报错
+    #[tokio::test]
+    async fn unknown_commands_pass_through() {
+        let mut props = AppProperties::default();
+        props.rate_limit.user_capacity = 1.0;
+        props.rate_limit.user_refill = 0.0;
+        let m = middleware(props).await;
+
+        let dm = Address::new(ChatPlatform::Onebot, 42i64, false);
+        let mut unknown = MessageIn::new(dm.clone(), 42i64, "/nope".to_string());
+        assert_eq!(limited(m.before(&mut unknown).await), None);
+        assert_eq!(limited(m.before(&mut unknown).await), None);
+        let mut ping = MessageIn::new(dm, 42i64, "/ping".to_string());
+        assert_eq!(limited(m.before(&mut ping).await), None);
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -7,28 +8,214 @@ pub enum ChatPlatform {
     Onebot,
 }
 
+/// 平台内的会话 / 用户标识；Telegram、Discord、QQ 群为整数，QQ 频道、Matrix 房间等为字符串
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ChatId {
+    Int(i64),
+    Str(String),
+}
+
+impl ChatId {
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ChatId::Int(i) => Some(*i),
+            ChatId::Str(s) => s.parse().ok(),
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            ChatId::Int(i) => u64::try_from(*i).ok(),
+            ChatId::Str(s) => s.parse().ok(),
+        }
+    }
+}
+
+impl fmt::Display for ChatId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChatId::Int(i) => write!(f, "{i}"),
+            ChatId::Str(s) => f.write_str(s),
+        }
+    }
+}
+
+impl From<i64> for ChatId {
+    fn from(v: i64) -> Self {
+        ChatId::Int(v)
+    }
+}
+
+impl From<u64> for ChatId {
+    fn from(v: u64) -> Self {
+        // Discord snowflake 不会超过 i63
+        ChatId::Int(v as i64)
+    }
+}
+
+impl From<String> for ChatId {
+    fn from(v: String) -> Self {
+        ChatId::Str(v)
+    }
+}
+
+impl From<&str> for ChatId {
+    fn from(v: &str) -> Self {
+        ChatId::Str(v.to_string())
+    }
+}
+
+/// 跨平台唯一的会话键，用于按会话加锁、存储等。
+/// 含会话类型：QQ 的私聊以对方 QQ 号为 id，可能与同号的群相同。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChatKey {
+    pub platform: ChatPlatform,
+    pub is_group: bool,
+    pub chat_id: ChatId,
+}
+
+impl ChatKey {
+    fn kind(&self) -> &'static str {
+        if self.is_group { "group" } else { "private" }
+    }
+
+    /// 配置中引用该会话的写法（小写），如 `onebot:group:123`
+    pub fn config_name(&self) -> String {
+        self.to_string().to_lowercase()
+    }
+}
+
+impl fmt::Display for ChatKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}:{}:{}", self.platform, self.kind(), self.chat_id)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Address {
     pub platform: ChatPlatform,
-    pub chat_id: i64,
+    pub chat_id: ChatId,
     pub is_group: bool,
+    /// Telegram 论坛话题 / Discord 子区等
+    pub thread_id: Option<ChatId>,
+    /// Discord 服务器 / QQ 频道
+    pub guild_id: Option<ChatId>,
+    /// 平台的一次性回复凭据（Discord 斜杠命令的交互 token）；存在时 sender 以交互回复发送
+    pub reply_token: Option<String>,
 }
 
 impl Address {
-    pub fn new(platform: ChatPlatform, chat_id: i64, is_group: bool) -> Self {
+    pub fn new(platform: ChatPlatform, chat_id: impl Into<ChatId>, is_group: bool) -> Self {
         Self {
             platform,
-            chat_id,
+            chat_id: chat_id.into(),
             is_group,
+            thread_id: None,
+            guild_id: None,
+            reply_token: None,
+        }
+    }
+
+    pub fn with_thread(mut self, thread_id: impl Into<ChatId>) -> Self {
+        self.thread_id = Some(thread_id.into());
+        self
+    }
+
+    pub fn with_guild(mut self, guild_id: impl Into<ChatId>) -> Self {
+        self.guild_id = Some(guild_id.into());
+        self
+    }
+
+    pub fn key(&self) -> ChatKey {
+        ChatKey {
+            platform: self.platform,
+            is_group: self.is_group,
+            chat_id: self.chat_id.clone(),
         }
     }
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct SenderProfile {
+    pub display_name: Option<String>,
+    /// 平台用户名（不含 `@`），核心暂不读取，留给命令使用
+    #[allow(dead_code)]
+    pub username: Option<String>,
+    /// 客户端语言（Telegram `language_code`）
+    pub language: Option<String>,
+    pub is_bot: bool,
+    /// 事件自带的管理员身份（OneBot `sender.role`、Discord 交互的成员权限）；`None` 时按需向平台查询
+    pub is_chat_admin: Option<bool>,
+}
+
+/// 被回复（引用）的消息；由各平台接收端填写，核心暂不读取，留给命令使用
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct ReplyRef {
+    pub message_id: ChatId,
+    pub user_id: Option<i64>,
+    pub text: Option<String>,
+}
+
+/// 消息中提及的用户；Telegram 的 `@username` 提及只有用户名
+#[derive(Debug, Clone)]
+pub struct Mention {
+    pub user_id: Option<i64>,
+    #[allow(dead_code)]
+    pub username: Option<String>,
+}
+
+/// 收到的附件种类；只有附件没有文字的消息也会交给核心
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InContentType {
+    Photo,
+    Document,
+    Sticker,
+    Audio,
+    Video,
+    Voice,
+}
+
+/// 收到的附件；`file_id` 为平台内的文件标识（Telegram file_id / Discord attachment id）
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct InAttachment {
+    pub ty: InContentType,
+    pub file_id: String,
+    pub name: Option<String>,
+    pub mime: Option<String>,
+    pub size: Option<u64>,
+    pub url: Option<String>,
+}
+
+/// 平台层识别出的、不依赖命令前缀的命令写法；对应的提及 / 后缀已从 `text` 中去掉
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Trigger {
+    #[default]
+    None,
+    /// 以提及 bot 开头，如 `@bot help`；是否视为命令由 `mention-prefix` 决定
+    Mention,
+    /// 明确发给本 bot 的命令，如 Telegram 的 `/help@bot`、Discord 斜杠命令
+    Explicit,
+}
+
 #[derive(Debug, Clone)]
 pub struct MessageIn {
     pub addr: Address,
     pub user_id: Option<i64>,
     pub text: String,
+
+    pub message_id: Option<ChatId>,
+    pub reply_to: Option<ReplyRef>,
+    pub mentions: Vec<Mention>,
+    pub mentioned_bot: bool,
+    pub trigger: Trigger,
+    pub attachments: Vec<InAttachment>,
+    pub sender: SenderProfile,
+    /// Unix 时间戳（秒）
+    pub timestamp: Option<i64>,
+    /// 按钮回调数据（Telegram callback query / Discord 组件）；此时 `message_id` 为按钮所在的消息
+    pub callback: Option<String>,
 }
 
 impl MessageIn {
@@ -37,6 +224,15 @@ impl MessageIn {
             addr,
             user_id: user_id.into(),
             text,
+            message_id: None,
+            reply_to: None,
+            mentions: vec![],
+            mentioned_bot: false,
+            trigger: Trigger::None,
+            attachments: vec![],
+            sender: SenderProfile::default(),
+            timestamp: None,
+            callback: None,
         }
     }
 }
@@ -96,21 +292,166 @@ impl Attachment {
             mime: Some(mime.into()),
         }
     }
+
+    /// 上传时的文件名；未命名时按类型和 MIME 子类型补一个，如 `image.png`
+    pub fn file_name(&self) -> String {
+        if let Some(n) = self.name.as_deref().filter(|n| !n.is_empty()) {
+            return n.to_string();
+        }
+        let stem = match self.ty {
+            OutContentType::Image => "image",
+            OutContentType::File => "file",
+        };
+        let ext = self
+            .mime
+            .as_deref()
+            .and_then(|m| m.split_once('/'))
+            .map(|(_, sub)| sub.split(['+', ';']).next().unwrap_or(sub).trim())
+            .filter(|e| !e.is_empty())
+            .unwrap_or("bin");
+        format!("{stem}.{ext}")
+    }
+}
+
+/// 出站消息的组成片段，由各平台的渲染器转换为 Telegram HTML / Discord markdown / OneBot 消息段
+#[derive(Debug, Clone)]
+pub enum Segment {
+    Text(String),
+    Bold(String),
+    // 以下几种由命令自行构造，核心与内置命令暂未使用
+    #[allow(dead_code)]
+    Italic(String),
+    Code(String),
+    CodeBlock { lang: Option<String>, code: String },
+    Link { text: String, url: String },
+    #[allow(dead_code)]
+    MentionUser { user_id: i64, name: Option<String> },
+    /// 引用块
+    #[allow(dead_code)]
+    Quote(String),
+    Attachment(Attachment),
+    LineBreak,
+}
+
+impl Segment {
+    pub fn text(s: impl Into<String>) -> Self {
+        Segment::Text(s.into())
+    }
+
+    pub fn bold(s: impl Into<String>) -> Self {
+        Segment::Bold(s.into())
+    }
+
+    pub fn code(s: impl Into<String>) -> Self {
+        Segment::Code(s.into())
+    }
+
+    pub fn link(text: impl Into<String>, url: impl Into<String>) -> Self {
+        Segment::Link {
+            text: text.into(),
+            url: url.into(),
+        }
+    }
+}
+
+/// 内联按钮；按下时 `data` 作为 `MessageIn::callback` 回传
+#[derive(Debug, Clone)]
+pub struct Button {
+    pub label: String,
+    pub data: String,
+}
+
+impl Button {
+    pub fn new(label: impl Into<String>, data: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            data: data.into(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct MessageOut {
     pub addr: Address,
-    pub text: Option<String>,
-    pub attachments: Vec<Attachment>,
+    pub segments: Vec<Segment>,
+    /// 要引用回复的消息 id；`None` 时直接发送到会话
+    pub reply_to: Option<ChatId>,
+    /// 按钮行；消息被拆分时放在最后一条上
+    pub keyboard: Vec<Vec<Button>>,
+    /// 分页结果的全部页，`segments` 为当前页；发送后由 `Paginator` 按消息 id 登记
+    pub pages: Vec<Vec<Segment>>,
 }
 
 impl MessageOut {
-    pub fn text(addr: Address, text: impl Into<String>) -> Self {
+    pub fn new(addr: Address, segments: Vec<Segment>) -> Self {
         Self {
             addr,
-            text: Some(text.into()),
-            attachments: vec![],
+            segments,
+            reply_to: None,
+            keyboard: vec![],
+            pages: vec![],
+        }
+    }
+
+    pub fn text(addr: Address, text: impl Into<String>) -> Self {
+        Self::new(addr, vec![Segment::Text(text.into())])
+    }
+
+    pub fn attachment(addr: Address, a: Attachment) -> Self {
+        Self::new(addr, vec![Segment::Attachment(a)])
+    }
+
+    pub fn attachments(&self) -> impl Iterator<Item = &Attachment> {
+        self.segments.iter().filter_map(|s| match s {
+            Segment::Attachment(a) => Some(a),
+            _ => None,
+        })
+    }
+
+    pub fn plain_text(&self) -> String {
+        plain_text(&self.segments)
+    }
+}
+
+/// 去掉所有格式的纯文本，用于日志、长度计算和不支持富文本的平台
+pub fn plain_text(segments: &[Segment]) -> String {
+    let mut sb = String::new();
+    for s in segments {
+        match s {
+            Segment::Text(t) | Segment::Bold(t) | Segment::Italic(t) | Segment::Code(t) => {
+                sb.push_str(t)
+            }
+            Segment::Quote(t) => {
+                for line in t.lines() {
+                    sb.push_str("> ");
+                    sb.push_str(line);
+                    sb.push('\n');
+                }
+            }
+            Segment::CodeBlock { code, .. } => sb.push_str(code),
+            Segment::Link { text, url } if text == url || text.is_empty() => sb.push_str(url),
+            Segment::Link { text, url } => sb.push_str(&format!("{text} ({url})")),
+            Segment::MentionUser { user_id, name } => match name {
+                Some(n) => sb.push_str(&format!("@{n}")),
+                None => sb.push_str(&format!("@{user_id}")),
+            },
+            Segment::Attachment(_) => {}
+            Segment::LineBreak => sb.push('\n'),
         }
     }
+    sb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chat_key_separates_private_and_group() {
+        let dm = Address::new(ChatPlatform::Onebot, 123456i64, false).key();
+        let group = Address::new(ChatPlatform::Onebot, 123456i64, true).key();
+        assert_ne!(dm, group);
+        assert_eq!(dm.to_string(), "Onebot:private:123456");
+        assert_eq!(group.config_name(), "onebot:group:123456");
+    }
 }
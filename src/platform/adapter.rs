@@ -0,0 +1,38 @@
+// src/platform/adapter.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::core::CommandRegistry;
+use crate::core::message_sender_hub::Sender;
+use crate::model::ChatPlatform;
+
+use super::InSink;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlatformHealth {
+    /// 尚未启动或已关闭
+    Stopped,
+    Running,
+    /// 已启动但与平台的连接中断（等待重连）
+    Disconnected,
+}
+
+/// 聊天平台适配器：接收消息写入 `InSink`，并提供对应平台的 `Sender`。
+#[async_trait]
+pub trait PlatformAdapter: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn platform(&self) -> ChatPlatform;
+
+    async fn bind(&self, sink: InSink);
+    async fn start(&self) -> Result<()>;
+    async fn sender(&self) -> Result<Arc<dyn Sender>>;
+    async fn shutdown(&self);
+
+    /// 把命令列表同步为平台原生命令（Discord 斜杠命令等）；默认不支持
+    async fn sync_commands(&self, _registry: &CommandRegistry) -> Result<()> {
+        Ok(())
+    }
+
+    fn health(&self) -> PlatformHealth;
+}
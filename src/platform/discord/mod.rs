@@ -1,5 +1,23 @@
 pub mod receiver;
+pub mod render;
 pub mod sender;
+pub mod slash;
 pub mod stack;
 
 pub use receiver::DiscordReceiver;
+
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::config::AppProperties;
+use crate::platform::PlatformAdapter;
+
+pub fn from_props(props: &AppProperties) -> Result<Option<Arc<dyn PlatformAdapter>>> {
+    if !props.discord.enabled {
+        return Ok(None);
+    }
+    Ok(Some(Arc::new(DiscordReceiver::new(
+        props.discord.clone(),
+        props.proxy.clone(),
+    ))))
+}
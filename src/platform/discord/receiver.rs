@@ -1,10 +1,11 @@
-use crate::config::ProxyConfig;
+use crate::config::{Discord, ProxyConfig};
+use crate::core::CommandRegistry;
 use crate::core::message_sender_hub::Sender;
-use crate::lifecycle::Closeable;
-use crate::model::MessageIn;
+use crate::model::ChatPlatform;
+use crate::platform::{InSink, PlatformAdapter, PlatformHealth};
 use anyhow::Result;
+use async_trait::async_trait;
 use std::sync::Arc;
-use tokio::sync::mpsc;
 
 use super::sender::DiscordSender;
 use super::stack::DiscordStack;
@@ -14,26 +15,45 @@ pub struct DiscordReceiver {
 }
 
 impl DiscordReceiver {
-    pub fn new(token: String, proxy_config: ProxyConfig) -> Self {
+    pub fn new(cfg: Discord, proxy_config: ProxyConfig) -> Self {
         Self {
-            stack: DiscordStack::new(token, proxy_config),
+            stack: DiscordStack::new(cfg, proxy_config),
         }
     }
+}
+
+#[async_trait]
+impl PlatformAdapter for DiscordReceiver {
+    fn name(&self) -> &'static str {
+        "DiscordReceiver"
+    }
 
-    pub async fn bind(&self, sink: mpsc::UnboundedSender<MessageIn>) {
+    fn platform(&self) -> ChatPlatform {
+        ChatPlatform::Discord
+    }
+
+    async fn bind(&self, sink: InSink) {
         self.stack.set_sink(sink).await;
     }
 
-    pub async fn start(&self) -> Result<()> {
+    async fn start(&self) -> Result<()> {
         self.stack.ensure_started().await
     }
 
-    pub async fn sender(&self) -> Result<Arc<dyn Sender>> {
+    async fn sender(&self) -> Result<Arc<dyn Sender>> {
         self.start().await?;
         Ok(Arc::new(DiscordSender::new(self.stack.clone())))
     }
-}
 
-impl Closeable for DiscordReceiver {
-    fn close(&self) {}
+    async fn sync_commands(&self, registry: &CommandRegistry) -> Result<()> {
+        self.stack.publish_commands(registry).await
+    }
+
+    async fn shutdown(&self) {
+        self.stack.shutdown().await;
+    }
+
+    fn health(&self) -> PlatformHealth {
+        self.stack.health()
+    }
 }
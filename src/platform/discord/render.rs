@@ -0,0 +1,129 @@
+// src/platform/discord/render.rs
+use crate::model::Segment;
+
+/// 渲染为 Discord markdown
+pub fn render_markdown(segs: &[Segment]) -> String {
+    let mut sb = String::new();
+    for s in segs {
+        match s {
+            Segment::Text(t) => sb.push_str(&escape(t)),
+            Segment::Bold(t) => sb.push_str(&format!("**{}**", escape(t))),
+            Segment::Italic(t) => sb.push_str(&format!("*{}*", escape(t))),
+            Segment::Code(t) => sb.push_str(&inline_code(t)),
+            Segment::CodeBlock { lang, code } => {
+                if !sb.is_empty() && !sb.ends_with('\n') {
+                    sb.push('\n');
+                }
+                // 代码块内无法转义 ```，插入零宽空格打断
+                let code = code.replace("```", "`\u{200b}``");
+                sb.push_str(&format!(
+                    "```{}\n{}\n```\n",
+                    lang.as_deref().unwrap_or(""),
+                    code
+                ));
+            }
+            Segment::Link { text, url } if text.is_empty() || text == url => {
+                sb.push_str(url);
+            }
+            Segment::Link { text, url } => {
+                sb.push_str(&format!("[{}](<{}>)", escape(text), url));
+            }
+            Segment::MentionUser { user_id, .. } => sb.push_str(&format!("<@{user_id}>")),
+            Segment::Quote(t) => {
+                if !sb.is_empty() && !sb.ends_with('\n') {
+                    sb.push('\n');
+                }
+                for line in t.lines() {
+                    sb.push_str("> ");
+                    sb.push_str(&escape(line));
+                    sb.push('\n');
+                }
+            }
+            Segment::Attachment(_) => {}
+            Segment::LineBreak => sb.push('\n'),
+        }
+    }
+    sb
+}
+
+/// 转义 markdown 字符；裸链接原样保留，否则 Discord 无法识别
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("https://") || rest.starts_with("http://") {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+        if matches!(c, '\\' | '*' | '_' | '~' | '`' | '|' | '>' | '#' | '[' | ']') {
+            out.push('\\');
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// 内容含反引号时改用双反引号包裹
+fn inline_code(s: &str) -> String {
+    if s.contains('`') {
+        format!("`` {s} ``")
+    } else {
+        format!("`{s}`")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_markdown_but_keeps_bare_links() {
+        let segs = vec![
+            Segment::text("*a* _b_ ~c~ `d` |e| > # [f] \\ see https://x.io/a_b*c done_"),
+            Segment::Bold("**x**".into()),
+            Segment::Italic("y_z".into()),
+        ];
+        assert_eq!(
+            render_markdown(&segs),
+            "\\*a\\* \\_b\\_ \\~c\\~ \\`d\\` \\|e\\| \\> \\# \\[f\\] \\\\ see https://x.io/a_b*c done\\_\
+             **\\*\\*x\\*\\***\
+             *y\\_z*"
+        );
+    }
+
+    #[test]
+    fn code_links_quotes_and_mentions() {
+        let segs = vec![
+            Segment::Code("a`b".into()),
+            Segment::Code("*raw*".into()),
+            Segment::CodeBlock {
+                lang: Some("sh".into()),
+                code: "echo ```".into(),
+            },
+            Segment::Link {
+                text: "[docs]".into(),
+                url: "https://x.io".into(),
+            },
+            Segment::Link {
+                text: String::new(),
+                url: "https://y.io".into(),
+            },
+            Segment::Quote("q*1\nq2".into()),
+            Segment::MentionUser {
+                user_id: 42,
+                name: None,
+            },
+        ];
+        assert_eq!(
+            render_markdown(&segs),
+            "`` a`b ```*raw*`\n\
+             ```sh\necho `\u{200b}``\n```\n\
+             [\\[docs\\]](<https://x.io>)https://y.io\n\
+             > q\\*1\n> q2\n\
+             <@42>"
+        );
+    }
+}
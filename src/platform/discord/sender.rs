@@ -1,11 +1,17 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use serenity::all::{ChannelId, CreateAttachment, CreateEmbed, CreateMessage, Http, UserId};
+use serenity::all::{
+    Builder, ButtonStyle, ChannelId, CreateActionRow, CreateAttachment, CreateButton, CreateEmbed,
+    CreateInteractionResponseFollowup, CreateMessage, EditMessage, GuildId, Http, MessageId,
+    MessageReference, UserId,
+};
 use std::sync::Arc;
 
+use crate::core::chunker;
 use crate::core::message_sender_hub::Sender;
-use crate::model::{MessageOut, OutContentType};
+use crate::model::{Address, Button, ChatId, MessageOut, OutContentType};
 
+use super::render::render_markdown;
 use super::stack::DiscordStack;
 
 pub struct DiscordSender {
@@ -14,94 +20,180 @@ pub struct DiscordSender {
 
 impl DiscordSender {
     const MAX_CONTENT: usize = 2000;
-    const MAX_EMBED_DESC: usize = 4096;
 
     pub fn new(stack: Arc<DiscordStack>) -> Self {
         Self {
-            http: Arc::new(Http::new(&stack.token)),
+            http: stack.http.clone(),
         }
     }
 
-    #[allow(clippy::needless_pass_by_value)]
-    async fn send_to_channel(
-        &self,
-        ch: ChannelId,
-        content: String,
-        embeds: Vec<CreateEmbed>,
-        files: Vec<CreateAttachment>,
-    ) -> Result<()> {
-        if content.chars().count() <= Self::MAX_CONTENT {
-            let mut msg = CreateMessage::new();
-            if !content.is_empty() {
-                msg = msg.content(content);
+    async fn send_part(&self, ch: ChannelId, out: MessageOut) -> Result<ChatId> {
+        // 对齐 Java：bytes -> upload；image url -> embed image :contentReference[oaicite:38]{index=38}
+        let mut files: Vec<CreateAttachment> = vec![];
+        let mut embeds: Vec<CreateEmbed> = vec![];
+
+        for a in out.attachments() {
+            if let Some(bytes) = &a.bytes {
+                files.push(CreateAttachment::bytes((**bytes).clone(), a.file_name()));
+            } else if a.ty == OutContentType::Image {
+                if let Some(url) = &a.url {
+                    if !url.trim().is_empty() {
+                        embeds.push(CreateEmbed::new().image(url));
+                    }
+                }
+            }
+        }
+
+        let content = render_markdown(&out.segments);
+
+        // 斜杠命令的输出作为交互 followup 发送，无需引用原消息
+        if let Some(token) = &out.addr.reply_token {
+            let mut f = CreateInteractionResponseFollowup::new();
+            if !content.trim().is_empty() {
+                f = f.content(content);
             }
             if !embeds.is_empty() {
-                msg = msg.embeds(embeds);
+                f = f.embeds(embeds);
             }
             if !files.is_empty() {
-                msg = msg.files(files);
+                f = f.add_files(files);
+            }
+            if !out.keyboard.is_empty() {
+                f = f.components(components_of(&out.keyboard));
             }
-            ch.send_message(&*self.http, msg).await?;
-            return Ok(());
+            let sent = f.execute(&*self.http, (None, token.as_str())).await?;
+            return Ok(sent.id.get().into());
         }
 
-        // 超长：拆 4096 一段进 embed desc（对齐 Java） :contentReference[oaicite:37]{index=37}
-        let chars: Vec<char> = content.chars().collect();
-        let mut merged = embeds;
-
-        let mut start = 0;
-        while start < chars.len() {
-            let end = usize::min(chars.len(), start + Self::MAX_EMBED_DESC);
-            let part: String = chars[start..end].iter().collect();
-            merged.push(CreateEmbed::new().description(part));
-            start = end;
+        let mut msg = CreateMessage::new();
+        if let Some(reference) = reference_of(ch, &out) {
+            msg = msg.reference_message(reference);
+        }
+        if !content.trim().is_empty() {
+            msg = msg.content(content);
+        }
+        if !embeds.is_empty() {
+            msg = msg.embeds(embeds);
         }
-
-        let mut msg = CreateMessage::new().content("");
-        msg = msg.embeds(merged);
         if !files.is_empty() {
             msg = msg.files(files);
         }
-        ch.send_message(&*self.http, msg).await?;
-        Ok(())
+        if !out.keyboard.is_empty() {
+            msg = msg.components(components_of(&out.keyboard));
+        }
+        let sent = ch.send_message(&*self.http, msg).await?;
+        Ok(sent.id.get().into())
     }
 }
 
 #[async_trait]
 impl Sender for DiscordSender {
-    async fn send(&self, out: MessageOut) -> Result<()> {
-        // 对齐 Java：bytes -> upload；image url -> embed image :contentReference[oaicite:38]{index=38}
-        let mut files: Vec<CreateAttachment> = vec![];
-        let mut embeds: Vec<CreateEmbed> = vec![];
+    async fn send(&self, out: MessageOut) -> Result<Option<ChatId>> {
+        let ch = channel_of(&out.addr)?;
+        let parts = chunker::split(out, Self::MAX_CONTENT, |segs| {
+            render_markdown(segs).chars().count()
+        });
+        let mut last = None;
+        for part in parts {
+            last = Some(self.send_part(ch, part).await?);
+        }
+        Ok(last)
+    }
 
-        for a in &out.attachments {
-            if let Some(bytes) = &a.bytes {
-                let name = a.name.clone().unwrap_or_else(|| {
-                    if a.ty == OutContentType::Image {
-                        "image.bin".into()
-                    } else {
-                        "file.bin".into()
-                    }
-                });
-                files.push(CreateAttachment::bytes((**bytes).clone(), name));
-            } else if a.ty == OutContentType::Image {
-                if let Some(url) = &a.url {
-                    if !url.trim().is_empty() {
-                        embeds.push(CreateEmbed::new().image(url));
-                    }
-                }
-            }
+    /// 斜杠命令已 defer，没有输出时删掉“思考中”的原始响应
+    async fn discard(&self, addr: &Address) -> Result<()> {
+        if let Some(token) = &addr.reply_token {
+            self.http.delete_original_interaction_response(token).await?;
         }
+        Ok(())
+    }
 
-        let content = out.text.clone().unwrap_or_default();
+    async fn edit(&self, message_id: &ChatId, out: MessageOut) -> Result<()> {
+        let ch = channel_of(&out.addr)?;
+        let id = message_id
+            .as_u64()
+            .filter(|id| *id != 0)
+            .map(MessageId::new)
+            .ok_or_else(|| anyhow!("invalid discord message id: {message_id}"))?;
 
-        if out.addr.is_group {
-            let ch = ChannelId::new(out.addr.chat_id as u64);
-            self.send_to_channel(ch, content, embeds, files).await
-        } else {
-            let uid = UserId::new(out.addr.chat_id as u64);
-            let dm = uid.create_dm_channel(&*self.http).await?;
-            self.send_to_channel(dm.id, content, embeds, files).await
-        }
+        let edit = EditMessage::new()
+            .content(render_markdown(&out.segments))
+            .components(components_of(&out.keyboard));
+        ch.edit_message(&*self.http, id, edit).await?;
+        Ok(())
+    }
+
+    fn supports_buttons(&self) -> bool {
+        true
+    }
+
+    /// 服务器所有者、管理员或拥有「管理服务器」权限的成员；私信中没有服务器，视为非管理员
+    async fn is_chat_admin(&self, addr: &Address, user_id: i64) -> Result<bool> {
+        let Some(guild) = addr.guild_id.as_ref().and_then(|g| g.as_u64()) else {
+            return Ok(false);
+        };
+        let guild = GuildId::new(guild);
+        let g = self.http.get_guild(guild).await?;
+        let member = self.http.get_member(guild, UserId::new(user_id as u64)).await?;
+        let p = g.member_permissions(&member);
+        Ok(p.administrator() || p.manage_guild())
+    }
+}
+
+/// 子区本身就是频道，优先发往子区
+fn channel_of(addr: &Address) -> Result<ChannelId> {
+    let target = addr.thread_id.as_ref().unwrap_or(&addr.chat_id);
+    target
+        .as_u64()
+        .filter(|id| *id != 0)
+        .map(ChannelId::new)
+        .ok_or_else(|| anyhow!("invalid discord channel id: {target}"))
+}
+
+/// 原消息已被删除时照常发送，只是不带引用
+fn reference_of(ch: ChannelId, out: &MessageOut) -> Option<MessageReference> {
+    let id = out
+        .reply_to
+        .as_ref()
+        .and_then(|id| id.as_u64())
+        .filter(|id| *id != 0)?;
+    Some(MessageReference::from((ch, MessageId::new(id))).fail_if_not_exists(false))
+}
+
+fn components_of(rows: &[Vec<Button>]) -> Vec<CreateActionRow> {
+    rows.iter()
+        .map(|row| {
+            CreateActionRow::Buttons(
+                row.iter()
+                    .map(|b| {
+                        CreateButton::new(b.data.clone())
+                            .label(b.label.clone())
+                            .style(ButtonStyle::Secondary)
+                    })
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ChatPlatform;
+
+    #[test]
+    fn reply_reference_tolerates_missing_message() {
+        let ch = ChannelId::new(5);
+        let mut out = MessageOut::text(Address::new(ChatPlatform::Discord, 5u64, true), "hi");
+        assert!(reference_of(ch, &out).is_none());
+
+        out.reply_to = Some(ChatId::Int(0));
+        assert!(reference_of(ch, &out).is_none());
+
+        out.reply_to = Some(ChatId::Int(9));
+        let r = reference_of(ch, &out).unwrap();
+        assert_eq!(r.message_id, Some(MessageId::new(9)));
+        assert_eq!(r.channel_id, ch);
+        assert_eq!(r.fail_if_not_exists, Some(false));
     }
 }
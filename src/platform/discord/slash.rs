@@ -0,0 +1,231 @@
+// src/platform/discord/slash.rs
+// 由命令树生成 Discord 应用命令，并把交互选项还原为文本命令行
+use serenity::all::{
+    CommandData, CommandDataOption, CommandDataOptionValue, CommandOptionType, CreateCommand,
+    CreateCommandOption,
+};
+use std::collections::HashMap;
+
+use crate::core::CommandRegistry;
+use crate::core::command_registry::BotCommand;
+use crate::core::command_tree::{ArgKind, NodeKind, TreeNode, command_tree};
+use crate::i18n;
+use crate::model::ChatPlatform;
+
+/// 命令路径（如 `github search`）-> 参数名，按 brigadier 中的顺序
+pub type ArgOrder = HashMap<String, Vec<String>>;
+
+/// 说明文字使用平台默认语言，其余内置语言作为 Discord 的本地化说明一并提交
+pub fn build(registry: &CommandRegistry) -> (Vec<CreateCommand>, ArgOrder) {
+    let lang = registry.settings().platform_language(ChatPlatform::Discord);
+    let mut order = ArgOrder::new();
+    let cmds = registry
+        .all()
+        .iter()
+        .filter(|c| c.visible())
+        .filter_map(|c| {
+            let tree = command_tree(c.as_ref())?;
+            Some(to_command(c.as_ref(), &tree, lang, &mut order))
+        })
+        .collect();
+    (cmds, order)
+}
+
+/// 字面量子节点映射为子命令（再下一层为子命令组），参数节点映射为带类型的选项。
+/// Discord 不允许带子命令的命令本身被调用，此时根节点上的参数会被忽略。
+fn to_command(
+    cmd: &dyn BotCommand,
+    tree: &TreeNode,
+    lang: &'static str,
+    order: &mut ArgOrder,
+) -> CreateCommand {
+    let name = option_name(&tree.name);
+    let subs: Vec<&TreeNode> = literals(tree).collect();
+
+    let options = if subs.is_empty() {
+        args_of(tree, &name, order)
+    } else {
+        subs.into_iter()
+            .map(|sub| {
+                let path = format!("{name} {}", option_name(&sub.name));
+                let leaves: Vec<&TreeNode> = literals(sub).collect();
+                if leaves.is_empty() {
+                    return sub_command(cmd, CommandOptionType::SubCommand, &sub.name, &path, lang)
+                        .set_sub_options(args_of(sub, &path, order));
+                }
+
+                let group = leaves.into_iter().map(|leaf| {
+                    let path = format!("{path} {}", option_name(&leaf.name));
+                    sub_command(cmd, CommandOptionType::SubCommand, &leaf.name, &path, lang)
+                        .set_sub_options(args_of(leaf, &path, order))
+                });
+                sub_command(cmd, CommandOptionType::SubCommandGroup, &sub.name, &path, lang)
+                    .set_sub_options(group)
+            })
+            .collect()
+    };
+
+    let mut out = CreateCommand::new(name).description(truncate(&cmd.description(lang), 100));
+    for l in i18n::locales().filter(|l| *l != lang) {
+        out = out.description_localized(discord_locale(l), truncate(&cmd.description(l), 100));
+    }
+    out.set_options(options)
+}
+
+/// 子命令 / 子命令组，说明取自各语言 usage 中对应的一行
+fn sub_command(
+    cmd: &dyn BotCommand,
+    kind: CommandOptionType,
+    name: &str,
+    path: &str,
+    lang: &'static str,
+) -> CreateCommandOption {
+    let mut opt =
+        CreateCommandOption::new(kind, option_name(name), describe(&cmd.usage(lang), path));
+    for l in i18n::locales().filter(|l| *l != lang) {
+        opt = opt.description_localized(discord_locale(l), describe(&cmd.usage(l), path));
+    }
+    opt
+}
+
+/// Discord 的 locale 为 `zh-CN`、`en-US` 这种写法
+fn discord_locale(locale: &str) -> String {
+    match locale.split_once('-') {
+        Some((lang, region)) => format!("{lang}-{}", region.to_uppercase()),
+        None => locale.to_string(),
+    }
+}
+
+fn literals(node: &TreeNode) -> impl Iterator<Item = &TreeNode> {
+    node.children
+        .iter()
+        .filter(|c| c.kind == NodeKind::Literal)
+}
+
+/// 沿参数链向下展开；分叉时取第一条。上一节点已可执行时，后续参数为可选。
+fn args_of(node: &TreeNode, path: &str, order: &mut ArgOrder) -> Vec<CreateCommandOption> {
+    let mut out = vec![];
+    let mut names = vec![];
+    let mut cur = node;
+
+    while let Some(next) = cur.children.iter().find(|c| c.kind != NodeKind::Literal) {
+        let NodeKind::Argument(kind) = next.kind else {
+            break;
+        };
+        let ty = match kind {
+            ArgKind::Text => CommandOptionType::String,
+            ArgKind::Integer => CommandOptionType::Integer,
+            ArgKind::Number => CommandOptionType::Number,
+            ArgKind::Boolean => CommandOptionType::Boolean,
+        };
+        let name = option_name(&next.name);
+        out.push(
+            CreateCommandOption::new(ty, name.clone(), truncate(&next.name, 100))
+                .required(!cur.executable),
+        );
+        names.push(name);
+        cur = next;
+
+        if out.len() == 25 {
+            break;
+        }
+    }
+
+    order.insert(path.to_string(), names);
+    out
+}
+
+/// 从 usage 中找到包含该路径的一行，取 `#` 或 ` - ` 之后的说明
+fn describe(usage: &str, path: &str) -> String {
+    let needle = format!("/{path}");
+    let line = usage.lines().find(|l| {
+        l.split_once(&needle)
+            .is_some_and(|(_, rest)| rest.is_empty() || rest.starts_with([' ', '`']))
+    });
+
+    let desc = line
+        .and_then(|l| {
+            l.split_once('#')
+                .or_else(|| l.split_once(" - "))
+                .map(|(_, d)| d.trim())
+        })
+        .filter(|d| !d.is_empty())
+        .unwrap_or(path);
+    truncate(desc, 100)
+}
+
+/// Discord 选项名只允许小写字母、数字、`-`、`_`，最长 32
+fn option_name(s: &str) -> String {
+    let name: String = s
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(32)
+        .collect();
+    if name.is_empty() { "_".into() } else { name }
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    s.chars().take(max).collect()
+}
+
+/// 把交互还原为 `name sub args...`；参数按注册顺序排列，不依赖 Discord 返回的顺序
+pub fn command_line(data: &CommandData, order: &ArgOrder) -> String {
+    let mut parts = vec![data.name.clone()];
+    push_options(&data.options, &mut parts, order);
+    parts.join(" ")
+}
+
+fn push_options(opts: &[CommandDataOption], parts: &mut Vec<String>, order: &ArgOrder) {
+    if let Some(sub) = opts.iter().find(|o| {
+        matches!(
+            o.value,
+            CommandDataOptionValue::SubCommand(_) | CommandDataOptionValue::SubCommandGroup(_)
+        )
+    }) {
+        parts.push(sub.name.clone());
+        if let CommandDataOptionValue::SubCommand(o) | CommandDataOptionValue::SubCommandGroup(o) =
+            &sub.value
+        {
+            push_options(o, parts, order);
+        }
+        return;
+    }
+
+    let path = parts.join(" ");
+    let rank = |name: &str| {
+        order
+            .get(&path)
+            .and_then(|names| names.iter().position(|n| n == name))
+            .unwrap_or(usize::MAX)
+    };
+    let mut sorted: Vec<&CommandDataOption> = opts.iter().collect();
+    sorted.sort_by_key(|o| rank(&o.name));
+
+    parts.extend(sorted.into_iter().map(|o| fmt_value(&o.value)));
+}
+
+fn fmt_value(v: &CommandDataOptionValue) -> String {
+    match v {
+        CommandDataOptionValue::Autocomplete { value, .. } => value.clone(),
+        CommandDataOptionValue::Boolean(b) => b.to_string(),
+        CommandDataOptionValue::Integer(i) => i.to_string(),
+        CommandDataOptionValue::Number(n) => n.to_string(),
+        CommandDataOptionValue::String(s) => s.clone(),
+
+        CommandDataOptionValue::Attachment(id) => id.get().to_string(),
+        CommandDataOptionValue::Channel(id) => id.get().to_string(),
+        CommandDataOptionValue::Mentionable(id) => id.get().to_string(),
+        CommandDataOptionValue::Role(id) => id.get().to_string(),
+        CommandDataOptionValue::User(id) => id.get().to_string(),
+
+        CommandDataOptionValue::Unknown(u) => format!("unknown({u})"),
+        _ => "<unsupported>".to_string(),
+    }
+}
@@ -1,40 +1,57 @@
 // src/platform/discord/stack.rs
 use anyhow::Result;
 use serenity::all::{
-    CommandDataOptionValue, Context, EventHandler, GatewayIntents, Interaction,
-    Message as DiscordMessage, Ready, async_trait,
+    Command, CommandInteraction, ComponentInteraction, Context, CreateInteractionResponse,
+    EventHandler, GatewayIntents, GuildId, Interaction, Message as DiscordMessage, Ready,
+    async_trait,
 };
 use serenity::client::ClientBuilder;
-use serenity::http::HttpBuilder;
+use serenity::http::{Http, HttpBuilder};
 use std::sync::{
     Arc,
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU64, Ordering},
 };
-use tokio::sync::{Mutex, RwLock, mpsc};
-use tracing::error;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
 
-use crate::config::ProxyConfig;
-use crate::model::{Address, ChatPlatform, MessageIn};
+use crate::config::{Discord, ProxyConfig};
+use crate::core::CommandRegistry;
+use crate::model::{
+    Address, ChatPlatform, InAttachment, InContentType, Mention, MessageIn, ReplyRef,
+    SenderProfile, Trigger,
+};
+use crate::platform::{InSink, PlatformHealth, strip_mention};
 
-pub type InSink = mpsc::UnboundedSender<MessageIn>;
+use super::slash::{self, ArgOrder};
 
 pub struct DiscordStack {
-    pub(crate) token: String,
+    pub(crate) cfg: Discord,
     pub(crate) proxy: ProxyConfig,
+    /// REST 客户端，由 sender 与斜杠命令注册共用；`ready` 后带上 application id
+    pub(crate) http: Arc<Http>,
+    arg_order: std::sync::RwLock<ArgOrder>,
 
     sink: RwLock<Option<InSink>>,
     started: AtomicBool,
-    shard_shutdown: Mutex<Option<serenity::gateway::ShardManager>>,
+    /// 由 `ready` 事件填充，用于判断是否被 @
+    bot_user_id: AtomicU64,
+    shard_shutdown: Mutex<Option<Arc<serenity::gateway::ShardManager>>>,
+    task: std::sync::Mutex<Option<JoinHandle<()>>>,
 }
 
 impl DiscordStack {
-    pub fn new(token: String, proxy: ProxyConfig) -> Arc<Self> {
+    pub fn new(cfg: Discord, proxy: ProxyConfig) -> Arc<Self> {
         Arc::new(Self {
-            token,
+            http: Arc::new(Http::new(&cfg.token)),
+            cfg,
             proxy,
+            arg_order: std::sync::RwLock::new(ArgOrder::new()),
             sink: RwLock::new(None),
             started: AtomicBool::new(false),
+            bot_user_id: AtomicU64::new(0),
             shard_shutdown: Mutex::new(None),
+            task: std::sync::Mutex::new(None),
         })
     }
 
@@ -60,22 +77,63 @@ impl DiscordStack {
             .apply_to_reqwest_builder(reqwest::Client::builder())?
             .build()?;
 
-        let http = HttpBuilder::new(&self.token).client(reqwest_client).build();
+        let http = HttpBuilder::new(&self.cfg.token).client(reqwest_client).build();
 
         let mut client = ClientBuilder::new_with_http(http, intents)
             .event_handler(handler)
             .await?;
 
-        let mgr = client.shard_manager.clone();
-        tokio::spawn(async move {
+        *self.shard_shutdown.lock().await = Some(client.shard_manager.clone());
+        let jh = tokio::spawn(async move {
             if let Err(e) = client.start().await {
                 error!("discord client start failed: {e:?}");
             }
         });
 
-        let _ = mgr;
+        *self.task.lock().unwrap() = Some(jh);
         Ok(())
     }
+
+    pub async fn shutdown(&self) {
+        if let Some(mgr) = self.shard_shutdown.lock().await.take() {
+            mgr.shutdown_all().await;
+        }
+        let jh = self.task.lock().unwrap().take();
+        if let Some(jh) = jh {
+            let _ = jh.await;
+        }
+        self.started.store(false, Ordering::SeqCst);
+    }
+
+    /// 由命令树生成斜杠命令并整体覆盖已注册的命令
+    pub async fn publish_commands(&self, registry: &CommandRegistry) -> Result<()> {
+        let (cmds, order) = slash::build(registry);
+        *self.arg_order.write().unwrap() = order;
+
+        if self.http.application_id().is_none() {
+            let app = self.http.get_current_application_info().await?;
+            self.http.set_application_id(app.id);
+        }
+
+        if self.cfg.command_guilds.is_empty() {
+            let n = Command::set_global_commands(&self.http, cmds).await?.len();
+            info!("discord: {n} global slash commands published");
+        } else {
+            for g in &self.cfg.command_guilds {
+                let n = GuildId::new(*g).set_commands(&self.http, cmds.clone()).await?.len();
+                info!("discord: {n} slash commands published to guild {g}");
+            }
+        }
+        Ok(())
+    }
+
+    pub fn health(&self) -> PlatformHealth {
+        match self.task.lock().unwrap().as_ref() {
+            None => PlatformHealth::Stopped,
+            Some(jh) if jh.is_finished() => PlatformHealth::Disconnected,
+            Some(_) => PlatformHealth::Running,
+        }
+    }
 }
 
 struct Handler {
@@ -88,100 +146,198 @@ impl EventHandler for Handler {
         if msg.author.bot {
             return;
         }
-        let text = msg.content.trim();
-        if text.is_empty() {
+        let bot_id = self.stack.bot_user_id.load(Ordering::Relaxed);
+        let Some(input) = to_message_in(&msg, bot_id) else {
             return;
-        }
-
-        let is_guild = msg.guild_id.is_some();
-        let chat_id = if is_guild {
-            msg.channel_id.get() as i64
-        } else {
-            msg.author.id.get() as i64
         };
-        let user_id = msg.author.id.get() as i64;
 
         if let Some(sink) = self.stack.sink.read().await.as_ref() {
-            let _ = sink.send(MessageIn::new(
-                Address::new(ChatPlatform::Discord, chat_id, is_guild),
-                Some(user_id),
-                text.to_string(),
-            ));
+            let _ = sink.send(input);
         }
     }
 
-    async fn ready(&self, _ctx: Context, _ready: Ready) {}
+    async fn ready(&self, _ctx: Context, ready: Ready) {
+        self.stack
+            .bot_user_id
+            .store(ready.user.id.get(), Ordering::Relaxed);
+        self.stack.http.set_application_id(ready.application.id);
+    }
 
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        let Some(cmd) = interaction.as_command() else {
-            return;
-        };
+        match interaction {
+            Interaction::Command(cmd) => self.on_command(&ctx, &cmd).await,
+            Interaction::Component(c) => self.on_component(&ctx, &c).await,
+            _ => {}
+        }
+    }
+}
+
+impl Handler {
+    /// 斜杠命令：先 defer，命令输出由 sender 以 followup 发送（第一条会替换“思考中”）
+    async fn on_command(&self, ctx: &Context, cmd: &CommandInteraction) {
         if cmd.user.bot {
             return;
         }
+        if let Err(e) = cmd.defer(&ctx.http).await {
+            warn!("discord defer interaction failed: {e:?}");
+            return;
+        }
 
-        let mut text = String::new();
-        text.push('/');
-        text.push_str(&cmd.data.name);
-        for opt in &cmd.data.options {
-            text.push(' ');
-            text.push_str(&fmt_cmd_value(&opt.value));
+        let text = slash::command_line(&cmd.data, &self.stack.arg_order.read().unwrap());
+
+        let mut addr = Address::new(
+            ChatPlatform::Discord,
+            cmd.channel_id.get(),
+            cmd.guild_id.is_some(),
+        );
+        if let Some(g) = cmd.guild_id {
+            addr = addr.with_guild(g.get());
         }
+        addr.reply_token = Some(cmd.token.clone());
 
-        let is_guild = cmd.guild_id.is_some();
-        let chat_id = if is_guild {
-            cmd.channel_id.get() as i64
-        } else {
-            cmd.user.id.get() as i64
+        let mut input = MessageIn::new(addr, Some(cmd.user.id.get() as i64), text);
+        input.trigger = Trigger::Explicit;
+        input.sender = SenderProfile {
+            display_name: cmd
+                .member
+                .as_ref()
+                .and_then(|m| m.nick.clone())
+                .or_else(|| cmd.user.global_name.clone())
+                .or_else(|| Some(cmd.user.name.clone())),
+            username: Some(cmd.user.name.clone()),
+            language: Some(cmd.locale.clone()),
+            is_bot: false,
+            // 交互自带成员在该服务器的权限，无需再查询
+            is_chat_admin: cmd
+                .member
+                .as_ref()
+                .and_then(|m| m.permissions)
+                .map(|p| p.administrator() || p.manage_guild()),
         };
-        let user_id = cmd.user.id.get() as i64;
 
         if let Some(sink) = self.stack.sink.read().await.as_ref() {
-            let _ = sink.send(MessageIn::new(
-                Address::new(ChatPlatform::Discord, chat_id, is_guild),
-                Some(user_id),
-                text,
-            ));
+            let _ = sink.send(input);
         }
+    }
 
-        let _ = cmd
-            .create_response(
-                &ctx.http,
-                serenity::all::CreateInteractionResponse::Message(
-                    serenity::all::CreateInteractionResponseMessage::new()
-                        .content("（推荐直接发送消息）")
-                        .ephemeral(true),
-                ),
-            )
+    /// 按钮点击：先确认交互（不改消息），实际编辑由核心通过 sender 完成
+    async fn on_component(&self, ctx: &Context, c: &ComponentInteraction) {
+        let _ = c
+            .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
             .await;
+
+        let mut addr = Address::new(
+            ChatPlatform::Discord,
+            c.channel_id.get(),
+            c.guild_id.is_some(),
+        );
+        if let Some(g) = c.guild_id {
+            addr = addr.with_guild(g.get());
+        }
+
+        let mut input = MessageIn::new(addr, Some(c.user.id.get() as i64), String::new());
+        input.message_id = Some(c.message.id.get().into());
+        input.callback = Some(c.data.custom_id.clone());
+
+        if let Some(sink) = self.stack.sink.read().await.as_ref() {
+            let _ = sink.send(input);
+        }
     }
 }
 
-fn fmt_cmd_value(v: &CommandDataOptionValue) -> String {
-    match v {
-        CommandDataOptionValue::Autocomplete { value, .. } => value.clone(),
-        CommandDataOptionValue::Boolean(b) => b.to_string(),
-        CommandDataOptionValue::Integer(i) => i.to_string(),
-        CommandDataOptionValue::Number(n) => n.to_string(),
-        CommandDataOptionValue::String(s) => s.clone(),
-
-        CommandDataOptionValue::SubCommand(opts)
-        | CommandDataOptionValue::SubCommandGroup(opts) => {
-            let mut out = String::new();
-            for o in opts {
-                out.push(' ');
-                out.push_str(&fmt_cmd_value(&o.value));
+fn to_message_in(msg: &DiscordMessage, bot_id: u64) -> Option<MessageIn> {
+    let mut text = msg.content.trim().to_string();
+    let mut trigger = Trigger::None;
+    // `<@bot> help`：去掉提及，交给核心按 `mention-prefix` 判断
+    if bot_id != 0
+        && let Some(rest) =
+            strip_mention(&text, &[&format!("<@{bot_id}>"), &format!("<@!{bot_id}>")])
+    {
+        text = rest.to_string();
+        trigger = Trigger::Mention;
+    }
+
+    let mut attachments: Vec<InAttachment> = msg
+        .attachments
+        .iter()
+        .map(|a| {
+            let is_image = a
+                .content_type
+                .as_deref()
+                .is_some_and(|m| m.starts_with("image/"));
+            InAttachment {
+                ty: if is_image {
+                    InContentType::Photo
+                } else {
+                    InContentType::Document
+                },
+                file_id: a.id.get().to_string(),
+                name: Some(a.filename.clone()),
+                mime: a.content_type.clone(),
+                size: Some(a.size as u64),
+                url: Some(a.url.clone()),
             }
-            out.trim().to_string()
-        }
+        })
+        .collect();
+    attachments.extend(msg.sticker_items.iter().map(|s| InAttachment {
+        ty: InContentType::Sticker,
+        file_id: s.id.get().to_string(),
+        name: Some(s.name.clone()),
+        mime: None,
+        size: None,
+        url: s.image_url(),
+    }));
+
+    if text.is_empty() && attachments.is_empty() {
+        return None;
+    }
 
-        CommandDataOptionValue::Attachment(id) => id.get().to_string(),
-        CommandDataOptionValue::Channel(id) => id.get().to_string(),
-        CommandDataOptionValue::Mentionable(id) => id.get().to_string(),
-        CommandDataOptionValue::Role(id) => id.get().to_string(),
-        CommandDataOptionValue::User(id) => id.get().to_string(),
+    // 私聊也使用 DM 频道 id，发送时无需再 create_dm_channel
+    let mut addr = Address::new(
+        ChatPlatform::Discord,
+        msg.channel_id.get(),
+        msg.guild_id.is_some(),
+    );
+    if let Some(g) = msg.guild_id {
+        addr = addr.with_guild(g.get());
+    }
+
+    let mut input = MessageIn::new(addr, Some(msg.author.id.get() as i64), text);
+    input.message_id = Some(msg.id.get().into());
+    input.timestamp = Some(msg.timestamp.unix_timestamp());
+    input.attachments = attachments;
+    input.trigger = trigger;
+    input.sender = SenderProfile {
+        display_name: msg
+            .member
+            .as_ref()
+            .and_then(|m| m.nick.clone())
+            .or_else(|| msg.author.global_name.clone())
+            .or_else(|| Some(msg.author.name.clone())),
+        username: Some(msg.author.name.clone()),
+        language: None,
+        is_bot: msg.author.bot,
+        is_chat_admin: None,
+    };
+    input.mentions = msg
+        .mentions
+        .iter()
+        .map(|u| Mention {
+            user_id: Some(u.id.get() as i64),
+            username: Some(u.name.clone()),
+        })
+        .collect();
 
-        CommandDataOptionValue::Unknown(u) => format!("unknown({u})"),
-        _ => "<unsupported>".to_string(),
+    if let Some(r) = &msg.referenced_message {
+        input.reply_to = Some(ReplyRef {
+            message_id: r.id.get().into(),
+            user_id: Some(r.author.id.get() as i64),
+            text: Some(r.content.clone()).filter(|t| !t.is_empty()),
+        });
     }
+
+    // 回复 bot 的消息时 Discord 默认也会把 bot 放进 mentions
+    input.mentioned_bot = bot_id != 0 && msg.mentions.iter().any(|u| u.id.get() == bot_id);
+
+    Some(input)
 }
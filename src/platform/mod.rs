@@ -1,2 +1,30 @@
+pub mod adapter;
 pub mod discord;
+pub mod onebot;
+pub mod registry;
 pub mod telegram;
+
+use tokio::sync::mpsc;
+
+use crate::model::MessageIn;
+
+pub use adapter::{PlatformAdapter, PlatformHealth};
+pub use registry::PlatformRegistry;
+
+pub type InSink = mpsc::UnboundedSender<MessageIn>;
+
+/// `@bot help` 这类以提及 bot 开头的消息：返回去掉提及后的文本。
+/// `mentions` 为 bot 在该平台上的各种提及写法，忽略大小写。
+pub fn strip_mention<'a>(text: &'a str, mentions: &[&str]) -> Option<&'a str> {
+    let text = text.trim_start();
+    mentions.iter().find_map(|m| {
+        let head = text.get(..m.len())?;
+        let rest = &text[m.len()..];
+        if m.is_empty() || !head.eq_ignore_ascii_case(m) || !rest.starts_with(char::is_whitespace)
+        {
+            return None;
+        }
+        let rest = rest.trim();
+        (!rest.is_empty()).then_some(rest)
+    })
+}
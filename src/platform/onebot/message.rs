@@ -0,0 +1,316 @@
+// src/platform/onebot/message.rs
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde_json::{Value, json};
+
+use crate::model::{
+    Attachment, ChatId, InAttachment, InContentType, Mention, MessageOut, OutContentType, Segment,
+    plain_text,
+};
+
+/// 统一为消息段数组：数组格式原样返回，字符串格式按 CQ 码拆分。
+pub fn segments_of(message: &Value) -> Vec<Value> {
+    match message {
+        Value::String(s) => parse_cq(s),
+        Value::Array(segs) => segs.clone(),
+        _ => vec![],
+    }
+}
+
+/// 拼接所有 `text` 段，得到纯文本。
+pub fn decode_text(segs: &[Value]) -> String {
+    segs.iter()
+        .filter(|s| s["type"] == "text")
+        .filter_map(|s| s["data"]["text"].as_str())
+        .collect::<String>()
+}
+
+/// `reply` 段引用的消息 id
+pub fn decode_reply(segs: &[Value]) -> Option<ChatId> {
+    segs.iter()
+        .find(|s| s["type"] == "reply")
+        .and_then(|s| match &s["data"]["id"] {
+            Value::Number(n) => n.as_i64().map(ChatId::Int),
+            Value::String(id) => Some(
+                id.parse::<i64>()
+                    .map_or_else(|_| ChatId::Str(id.clone()), ChatId::Int),
+            ),
+            _ => None,
+        })
+}
+
+/// `at` 段；`qq=all` 表示 @全体成员，不计入
+pub fn decode_mentions(segs: &[Value]) -> Vec<Mention> {
+    segs.iter()
+        .filter(|s| s["type"] == "at")
+        .filter_map(|s| {
+            let qq = match &s["data"]["qq"] {
+                Value::Number(n) => n.as_i64(),
+                Value::String(q) => q.parse().ok(),
+                _ => None,
+            }?;
+            Some(Mention {
+                user_id: Some(qq),
+                username: s["data"]["name"].as_str().map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// 消息是否以 @`qq` 开头（忽略前面的 `reply` 段与空白文本）
+pub fn starts_with_at(segs: &[Value], qq: i64) -> bool {
+    segs.iter()
+        .find(|s| {
+            let blank = s["type"] == "text"
+                && s["data"]["text"].as_str().is_some_and(|t| t.trim().is_empty());
+            s["type"] != "reply" && !blank
+        })
+        .filter(|s| s["type"] == "at")
+        .is_some_and(|s| match &s["data"]["qq"] {
+            Value::Number(n) => n.as_i64() == Some(qq),
+            Value::String(q) => q.parse() == Ok(qq),
+            _ => false,
+        })
+}
+
+pub fn decode_attachments(segs: &[Value]) -> Vec<InAttachment> {
+    segs.iter()
+        .filter_map(|s| {
+            let ty = match s["type"].as_str()? {
+                "image" => InContentType::Photo,
+                "file" => InContentType::Document,
+                "record" => InContentType::Voice,
+                "video" => InContentType::Video,
+                "mface" | "face" => InContentType::Sticker,
+                _ => return None,
+            };
+            let d = &s["data"];
+            let file_id = d["file_id"]
+                .as_str()
+                .or(d["file"].as_str())
+                .or(d["id"].as_str())
+                .map(str::to_string)
+                .or_else(|| d["id"].as_i64().map(|i| i.to_string()))?;
+            Some(InAttachment {
+                ty,
+                file_id,
+                name: d["name"].as_str().map(str::to_string),
+                mime: None,
+                size: match &d["file_size"] {
+                    Value::Number(n) => n.as_u64(),
+                    Value::String(n) => n.parse().ok(),
+                    _ => None,
+                },
+                url: d["url"].as_str().map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// 将 `MessageOut` 编码为 OneBot v11 消息段数组；QQ 不支持富文本，格式片段降级为纯文本。
+pub fn encode_segments(out: &MessageOut) -> Value {
+    let mut segs: Vec<Value> = vec![];
+    let mut text = String::new();
+
+    for s in &out.segments {
+        match s {
+            Segment::MentionUser { user_id, .. } => {
+                flush_text(&mut segs, &mut text);
+                segs.push(json!({ "type": "at", "data": { "qq": user_id.to_string() } }));
+            }
+            Segment::Attachment(a) => {
+                let Some(file) = attachment_file(a) else {
+                    continue;
+                };
+                flush_text(&mut segs, &mut text);
+                match a.ty {
+                    OutContentType::Image => {
+                        segs.push(json!({ "type": "image", "data": { "file": file } }));
+                    }
+                    OutContentType::File => {
+                        // 非 v11 标准段，NapCat / Lagrange 等实现支持
+                        let name = a.file_name();
+                        segs.push(json!({ "type": "file", "data": { "file": file, "name": name } }));
+                    }
+                }
+            }
+            other => text.push_str(&plain_text(std::slice::from_ref(other))),
+        }
+    }
+    flush_text(&mut segs, &mut text);
+
+    // reply 段需位于最前
+    if let Some(id) = &out.reply_to
+        && !segs.is_empty()
+    {
+        segs.insert(0, json!({ "type": "reply", "data": { "id": id.to_string() } }));
+    }
+
+    Value::Array(segs)
+}
+
+fn flush_text(segs: &mut Vec<Value>, text: &mut String) {
+    if !text.is_empty() {
+        let t = std::mem::take(text);
+        segs.push(json!({ "type": "text", "data": { "text": t } }));
+    }
+}
+
+fn attachment_file(a: &Attachment) -> Option<String> {
+    if let Some(bytes) = &a.bytes {
+        return Some(format!("base64://{}", BASE64.encode(bytes.as_slice())));
+    }
+    a.url.clone().filter(|u| !u.trim().is_empty())
+}
+
+/// `文本[CQ:type,k=v,...]文本` -> 消息段数组
+fn parse_cq(s: &str) -> Vec<Value> {
+    let mut segs = vec![];
+    let mut rest = s;
+
+    while let Some(start) = rest.find("[CQ:") {
+        push_text(&mut segs, &rest[..start]);
+        let Some(end) = rest[start..].find(']') else {
+            push_text(&mut segs, &rest[start..]);
+            return segs;
+        };
+
+        let body = &rest[start + 4..start + end];
+        let mut parts = body.split(',');
+        let ty = parts.next().unwrap_or_default();
+        let data: serde_json::Map<String, Value> = parts
+            .filter_map(|kv| kv.split_once('='))
+            .map(|(k, v)| (k.to_string(), Value::String(cq_unescape(v))))
+            .collect();
+        segs.push(json!({ "type": ty, "data": data }));
+
+        rest = &rest[start + end + 1..];
+    }
+    push_text(&mut segs, rest);
+    segs
+}
+
+fn push_text(segs: &mut Vec<Value>, t: &str) {
+    if !t.is_empty() {
+        segs.push(json!({ "type": "text", "data": { "text": cq_unescape(t) } }));
+    }
+}
+
+fn cq_unescape(s: &str) -> String {
+    s.replace("&#91;", "[")
+        .replace("&#93;", "]")
+        .replace("&#44;", ",")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cq_codes_split_into_segments() {
+        let segs = segments_of(&json!("[CQ:reply,id=42][CQ:at,qq=10001] hi &#91;x&#93;&amp;y"));
+        assert_eq!(segs.len(), 3);
+        assert_eq!(segs[0], json!({ "type": "reply", "data": { "id": "42" } }));
+        assert_eq!(segs[1], json!({ "type": "at", "data": { "qq": "10001" } }));
+        assert_eq!(decode_text(&segs), " hi [x]&y");
+        assert_eq!(decode_reply(&segs), Some(ChatId::Int(42)));
+        assert!(starts_with_at(&segs, 10001));
+        assert!(!starts_with_at(&segs, 10002));
+    }
+
+    #[test]
+    fn cq_escapes_inside_params_and_unclosed_codes() {
+        let segs = segments_of(&json!("[CQ:image,file=a&#44;b.png,url=http://x/?a=1&amp;b=2]tail[CQ:at"));
+        assert_eq!(segs[0]["data"]["file"], "a,b.png");
+        assert_eq!(segs[0]["data"]["url"], "http://x/?a=1&b=2");
+        assert_eq!(decode_text(&segs), "tail[CQ:at");
+
+        let atts = decode_attachments(&segs);
+        assert_eq!(atts.len(), 1);
+        assert_eq!(atts[0].file_id, "a,b.png");
+    }
+
+    #[test]
+    fn array_messages_and_mentions() {
+        let segs = segments_of(&json!([
+            { "type": "at", "data": { "qq": "all" } },
+            { "type": "at", "data": { "qq": 10001, "name": "bot" } },
+            { "type": "text", "data": { "text": "help" } },
+        ]));
+        let mentions = decode_mentions(&segs);
+        assert_eq!(mentions.len(), 1);
+        assert_eq!(mentions[0].user_id, Some(10001));
+        assert!(!starts_with_at(&segs, 10001));
+    }
+
+    #[test]
+    fn encode_puts_reply_first_and_attachments_as_segments() {
+        let addr = crate::model::Address::new(crate::model::ChatPlatform::Onebot, 1i64, true);
+        let mut out = MessageOut::new(
+            addr,
+            vec![
+                Segment::text("hi "),
+                Segment::Attachment(Attachment::file_bytes("a.txt", b"ok".to_vec(), "text/plain")),
+                Segment::Attachment(Attachment::image_bytes("p.png", b"ok".to_vec(), "image/png")),
+                Segment::Attachment(Attachment::image_url("https://x/y.png")),
+                Segment::bold("!"),
+            ],
+        );
+        out.reply_to = Some(ChatId::Int(9));
+        assert_eq!(
+            encode_segments(&out),
+            json!([
+                { "type": "reply", "data": { "id": "9" } },
+                { "type": "text", "data": { "text": "hi " } },
+                { "type": "file", "data": { "file": "base64://b2s=", "name": "a.txt" } },
+                { "type": "image", "data": { "file": "base64://b2s=" } },
+                { "type": "image", "data": { "file": "https://x/y.png" } },
+                { "type": "text", "data": { "text": "!" } },
+            ])
+        );
+    }
+
+    #[test]
+    fn encode_degrades_formatting_and_keeps_mentions() {
+        let addr = crate::model::Address::new(crate::model::ChatPlatform::Onebot, 1i64, true);
+        let out = MessageOut::new(
+            addr,
+            vec![
+                Segment::Italic("a".into()),
+                Segment::Quote("q1\nq2".into()),
+                Segment::MentionUser { user_id: 7, name: Some("bob".into()) },
+            ],
+        );
+        assert_eq!(
+            encode_segments(&out),
+            json!([
+                { "type": "text", "data": { "text": "a> q1\n> q2\n" } },
+                { "type": "at", "data": { "qq": "7" } },
+            ])
+        );
+    }
+
+    #[test]
+    fn encode_puts_reply_first_and_at_as_segment() {
+        let addr = crate::model::Address::new(crate::model::ChatPlatform::Onebot, 1i64, true);
+        let mut out = MessageOut::new(
+            addr,
+            vec![
+                Segment::text("hi "),
+                Segment::MentionUser { user_id: 7, name: None },
+                Segment::bold("!"),
+            ],
+        );
+        out.reply_to = Some(ChatId::Int(9));
+        assert_eq!(
+            encode_segments(&out),
+            json!([
+                { "type": "reply", "data": { "id": "9" } },
+                { "type": "text", "data": { "text": "hi " } },
+                { "type": "at", "data": { "qq": "7" } },
+                { "type": "text", "data": { "text": "!" } },
+            ])
+        );
+    }
+}
@@ -0,0 +1,24 @@
+// src/platform/onebot/mod.rs
+pub mod message;
+pub mod receiver;
+pub mod sender;
+pub mod server;
+pub mod stack;
+
+pub use receiver::OnebotReceiver;
+
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::config::AppProperties;
+use crate::platform::PlatformAdapter;
+
+pub fn from_props(props: &AppProperties) -> Result<Option<Arc<dyn PlatformAdapter>>> {
+    if !props.onebot.enabled {
+        return Ok(None);
+    }
+    Ok(Some(Arc::new(OnebotReceiver::new(
+        props.onebot.clone(),
+        props.proxy.clone(),
+    )?)))
+}
@@ -0,0 +1,54 @@
+use crate::config::{Onebot, ProxyConfig};
+use crate::core::message_sender_hub::Sender;
+use crate::model::ChatPlatform;
+use crate::platform::{InSink, PlatformAdapter, PlatformHealth};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::sender::OnebotSender;
+use super::stack::OnebotStack;
+
+pub struct OnebotReceiver {
+    stack: Arc<OnebotStack>,
+}
+
+impl OnebotReceiver {
+    pub fn new(cfg: Onebot, proxy: ProxyConfig) -> Result<Self> {
+        Ok(Self {
+            stack: OnebotStack::new(cfg, &proxy)?,
+        })
+    }
+}
+
+#[async_trait]
+impl PlatformAdapter for OnebotReceiver {
+    fn name(&self) -> &'static str {
+        "OnebotReceiver"
+    }
+
+    fn platform(&self) -> ChatPlatform {
+        ChatPlatform::Onebot
+    }
+
+    async fn bind(&self, sink: InSink) {
+        self.stack.set_sink(sink).await;
+    }
+
+    async fn start(&self) -> Result<()> {
+        self.stack.ensure_started().await
+    }
+
+    async fn sender(&self) -> Result<Arc<dyn Sender>> {
+        self.start().await?;
+        Ok(Arc::new(OnebotSender::new(self.stack.clone())))
+    }
+
+    async fn shutdown(&self) {
+        self.stack.shutdown();
+    }
+
+    fn health(&self) -> PlatformHealth {
+        self.stack.health()
+    }
+}
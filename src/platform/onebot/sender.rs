@@ -0,0 +1,101 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+use crate::core::chunker;
+use crate::core::message_sender_hub::Sender;
+use crate::model::{Address, ChatId, MessageOut, plain_text};
+
+use super::message::encode_segments;
+use super::stack::OnebotStack;
+
+pub struct OnebotSender {
+    stack: Arc<OnebotStack>,
+}
+
+impl OnebotSender {
+    /// QQ 单条消息的经验上限（字符）
+    const MAX_TEXT: usize = 3000;
+
+    pub fn new(stack: Arc<OnebotStack>) -> Self {
+        Self { stack }
+    }
+
+    async fn send_part(&self, out: MessageOut) -> Result<Option<ChatId>> {
+        let message = encode_segments(&out);
+        if message.as_array().is_some_and(|a| a.is_empty()) {
+            return Ok(None);
+        }
+
+        let chat_id = id_value(&out.addr.chat_id);
+
+        let data = if let Some(guild_id) = &out.addr.guild_id {
+            self.stack
+                .call_api(
+                    "send_guild_channel_msg",
+                    json!({
+                        "guild_id": guild_id.to_string(),
+                        "channel_id": out.addr.chat_id.to_string(),
+                        "message": message,
+                    }),
+                )
+                .await?
+        } else if out.addr.is_group {
+            self.stack
+                .call_api(
+                    "send_group_msg",
+                    json!({ "group_id": chat_id, "message": message }),
+                )
+                .await?
+        } else {
+            self.stack
+                .call_api(
+                    "send_private_msg",
+                    json!({ "user_id": chat_id, "message": message }),
+                )
+                .await?
+        };
+
+        Ok(match &data["message_id"] {
+            Value::Number(n) => n.as_i64().map(ChatId::Int),
+            Value::String(s) => Some(ChatId::Str(s.clone())),
+            _ => None,
+        })
+    }
+}
+
+#[async_trait]
+impl Sender for OnebotSender {
+    async fn send(&self, out: MessageOut) -> Result<Option<ChatId>> {
+        let parts = chunker::split(out, Self::MAX_TEXT, |segs| {
+            plain_text(segs).chars().count()
+        });
+        let mut last = None;
+        for part in parts {
+            last = self.send_part(part).await?;
+        }
+        Ok(last)
+    }
+
+    async fn is_chat_admin(&self, addr: &Address, user_id: i64) -> Result<bool> {
+        if !addr.is_group || addr.guild_id.is_some() {
+            return Ok(false);
+        }
+        let data = self
+            .stack
+            .call_api(
+                "get_group_member_info",
+                json!({ "group_id": id_value(&addr.chat_id), "user_id": user_id }),
+            )
+            .await?;
+        Ok(matches!(data["role"].as_str(), Some("owner" | "admin")))
+    }
+}
+
+fn id_value(id: &ChatId) -> Value {
+    match id {
+        ChatId::Int(i) => json!(i),
+        ChatId::Str(s) => json!(s),
+    }
+}
@@ -0,0 +1,360 @@
+// src/platform/onebot/server.rs
+// 反向 WS / HTTP POST 模式：bot 监听本地端口，由 OneBot 实现主动连入
+use anyhow::{Context, Result};
+use hmac::{Hmac, Mac};
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde_json::Value;
+use sha1::Sha1;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio_tungstenite::tungstenite::handshake::server::{
+    ErrorResponse, Request as WsRequest, Response as WsResponse,
+};
+use tracing::{error, info, warn};
+
+use super::stack::OnebotStack;
+
+/// HTTP 上报请求体上限，单条事件远小于此值
+const MAX_BODY: usize = 1024 * 1024;
+
+async fn bind(stack: &OnebotStack) -> Result<TcpListener> {
+    let addr = stack.cfg.listen.trim();
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("bind onebot listen address {addr}"))?;
+    info!("onebot {:?} listening on {addr}", stack.cfg.mode);
+    Ok(listener)
+}
+
+pub async fn serve_reverse_ws(stack: Arc<OnebotStack>) -> Result<JoinHandle<()>> {
+    let listener = bind(&stack).await?;
+
+    Ok(tokio::spawn(async move {
+        // 连接任务归属于监听任务，shutdown 中止监听任务时随 JoinSet 一起取消
+        let mut conns = JoinSet::new();
+        loop {
+            while conns.try_join_next().is_some() {}
+
+            let (tcp, peer) = match listener.accept().await {
+                Ok(c) => c,
+                Err(e) => {
+                    error!("onebot reverse ws accept failed: {e:?}");
+                    continue;
+                }
+            };
+
+            let stack = stack.clone();
+            conns.spawn(async move {
+                let auth = stack.clone();
+                // ErrorResponse 的体积由 tungstenite 决定
+                #[allow(clippy::result_large_err)]
+                let callback = move |req: &WsRequest, resp: WsResponse| {
+                    let header = req
+                        .headers()
+                        .get("Authorization")
+                        .and_then(|v| v.to_str().ok());
+                    if !auth.authorized(header, req.uri().query()) {
+                        let mut err = ErrorResponse::new(Some("unauthorized".into()));
+                        *err.status_mut() = StatusCode::UNAUTHORIZED;
+                        return Err(err);
+                    }
+                    Ok(resp)
+                };
+
+                match tokio_tungstenite::accept_hdr_async(tcp, callback).await {
+                    Ok(ws) => {
+                        info!("onebot reverse ws connected from {peer}");
+                        stack.run_session(ws).await;
+                        warn!("onebot reverse ws from {peer} closed");
+                    }
+                    Err(e) => warn!("onebot reverse ws handshake from {peer} failed: {e}"),
+                }
+            });
+        }
+    }))
+}
+
+pub async fn serve_http_post(stack: Arc<OnebotStack>) -> Result<JoinHandle<()>> {
+    let listener = bind(&stack).await?;
+
+    Ok(tokio::spawn(async move {
+        let mut conns = JoinSet::new();
+        loop {
+            while conns.try_join_next().is_some() {}
+
+            let (tcp, peer) = match listener.accept().await {
+                Ok(c) => c,
+                Err(e) => {
+                    error!("onebot http accept failed: {e:?}");
+                    continue;
+                }
+            };
+
+            let stack = stack.clone();
+            conns.spawn(async move {
+                let svc = service_fn(move |req| handle_post(stack.clone(), req));
+                if let Err(e) = http1::Builder::new()
+                    .serve_connection(TokioIo::new(tcp), svc)
+                    .await
+                {
+                    warn!("onebot http connection from {peer} failed: {e}");
+                }
+            });
+        }
+    }))
+}
+
+async fn handle_post(
+    stack: Arc<OnebotStack>,
+    req: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    if req.method() != Method::POST {
+        return Ok(status(StatusCode::METHOD_NOT_ALLOWED));
+    }
+
+    let signature = req
+        .headers()
+        .get("X-Signature")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let body = match Limited::new(req.into_body(), MAX_BODY).collect().await {
+        Ok(b) => b.to_bytes(),
+        Err(e) if e.is::<LengthLimitError>() => {
+            warn!("onebot http event rejected: body exceeds {MAX_BODY} bytes");
+            return Ok(status(StatusCode::PAYLOAD_TOO_LARGE));
+        }
+        Err(e) => {
+            warn!("onebot http read body failed: {e}");
+            return Ok(status(StatusCode::BAD_REQUEST));
+        }
+    };
+
+    if !verify_signature(&stack.cfg.secret, signature.as_deref(), &body) {
+        warn!("onebot http event rejected: bad X-Signature");
+        return Ok(status(StatusCode::UNAUTHORIZED));
+    }
+
+    match serde_json::from_slice::<Value>(&body) {
+        Ok(v) => stack.on_event(&v).await,
+        Err(e) => warn!("onebot http bad event: {e}"),
+    }
+
+    // 不使用快速操作，统一走 HTTP API 回复
+    Ok(status(StatusCode::NO_CONTENT))
+}
+
+/// `X-Signature: sha1=<hex(hmac_sha1(secret, body))>`；未配置 secret 时放行。
+fn verify_signature(secret: &str, header: Option<&str>, body: &[u8]) -> bool {
+    if secret.is_empty() {
+        return true;
+    }
+
+    let Some(sig) = header
+        .and_then(|h| h.strip_prefix("sha1="))
+        .and_then(|h| hex::decode(h).ok())
+    else {
+        return false;
+    };
+
+    let Ok(mut mac) = Hmac::<Sha1>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&sig).is_ok()
+}
+
+fn status(code: StatusCode) -> Response<Full<Bytes>> {
+    let mut resp = Response::new(Full::new(Bytes::new()));
+    *resp.status_mut() = code;
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Onebot, OnebotMode, ProxyConfig};
+    use futures_util::SinkExt;
+    use serde_json::json;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    use crate::model::MessageIn;
+    use tokio_tungstenite::tungstenite::Error as WsError;
+    use tokio_tungstenite::tungstenite::Message as WsMessage;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha1>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        format!("sha1={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    /// 先占一个空闲端口再释放，交给 `bind` 重新监听
+    async fn free_addr() -> String {
+        let l = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        l.local_addr().unwrap().to_string()
+    }
+
+    async fn start(cfg: Onebot) -> (Arc<OnebotStack>, mpsc::UnboundedReceiver<MessageIn>) {
+        let stack = OnebotStack::new(cfg, &ProxyConfig::default()).unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        stack.set_sink(tx).await;
+        stack.ensure_started().await.unwrap();
+        (stack, rx)
+    }
+
+    fn private_event(text: &str) -> String {
+        json!({
+            "post_type": "message",
+            "message_type": "private",
+            "time": 1700000000,
+            "self_id": 10001,
+            "user_id": 42,
+            "message_id": 7,
+            "message": text,
+            "sender": { "nickname": "alice" },
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn signature_checks() {
+        let body = br#"{"post_type":"meta_event"}"#;
+        let good = sign("s3cret", body);
+        assert!(verify_signature("s3cret", Some(&good), body));
+        assert!(!verify_signature(
+            "s3cret",
+            Some(&sign("other", body)),
+            body
+        ));
+        assert!(!verify_signature("s3cret", Some(&good), b"{}"));
+        assert!(!verify_signature("s3cret", Some("sha1=not-hex"), body));
+        assert!(!verify_signature(
+            "s3cret",
+            Some(good.trim_start_matches("sha1=")),
+            body
+        ));
+        assert!(!verify_signature("s3cret", None, body));
+        assert!(verify_signature("", None, body));
+    }
+
+    /// 带正确签名的上报进入 sink，签名错误或缺失返回 401 且不投递
+    #[tokio::test]
+    async fn http_post_verifies_signature() {
+        let listen = free_addr().await;
+        let (stack, mut rx) = start(Onebot {
+            enabled: true,
+            mode: OnebotMode::HttpPost,
+            listen: listen.clone(),
+            secret: "s3cret".to_string(),
+            ..Default::default()
+        })
+        .await;
+
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let url = format!("http://{listen}/");
+        let post = |body: String, sig: Option<String>| {
+            let mut req = client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .body(body);
+            if let Some(sig) = sig {
+                req = req.header("X-Signature", sig);
+            }
+            req.send()
+        };
+
+        let bad = private_event("/bad");
+        let resp = post(bad.clone(), Some(sign("other", bad.as_bytes())))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let resp = post(bad, None).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let good = private_event("/ping");
+        let sig = sign("s3cret", good.as_bytes());
+        let resp = post(good, Some(sig)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+        let input = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(input.text, "/ping");
+        assert!(rx.try_recv().is_err());
+        stack.shutdown();
+    }
+
+    #[tokio::test]
+    async fn http_post_rejects_oversized_body() {
+        let listen = free_addr().await;
+        let (stack, mut rx) = start(Onebot {
+            enabled: true,
+            mode: OnebotMode::HttpPost,
+            listen: listen.clone(),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = reqwest::Client::builder()
+            .no_proxy()
+            .build()
+            .unwrap()
+            .post(format!("http://{listen}/"))
+            .body(vec![b' '; MAX_BODY + 1])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(rx.try_recv().is_err());
+        stack.shutdown();
+    }
+
+    /// 反向 WS 握手时 token 不对返回 401，正确 token 可以连入并上报事件
+    #[tokio::test]
+    async fn reverse_ws_checks_access_token() {
+        let listen = free_addr().await;
+        let (stack, mut rx) = start(Onebot {
+            enabled: true,
+            mode: OnebotMode::ReverseWs,
+            listen: listen.clone(),
+            access_token: "secret-token".to_string(),
+            ..Default::default()
+        })
+        .await;
+
+        let request = |token: &str| {
+            let mut req = format!("ws://{listen}/").into_client_request().unwrap();
+            req.headers_mut()
+                .insert("Authorization", format!("Bearer {token}").parse().unwrap());
+            req
+        };
+
+        match tokio_tungstenite::connect_async(request("wrong")).await {
+            Err(WsError::Http(resp)) => assert_eq!(resp.status(), StatusCode::UNAUTHORIZED),
+            other => panic!("expected 401, got {other:?}"),
+        }
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(request("secret-token"))
+            .await
+            .unwrap();
+        ws.send(WsMessage::Text(private_event("/ping").into()))
+            .await
+            .unwrap();
+        let input = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(input.text, "/ping");
+        stack.shutdown();
+    }
+}
@@ -0,0 +1,559 @@
+// src/platform/onebot/stack.rs
+use anyhow::{Context, Result, anyhow};
+use futures_util::{SinkExt, StreamExt};
+use reqwest::header::CONTENT_TYPE;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, AtomicU64, Ordering},
+};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{RwLock, mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tracing::{debug, error, info, warn};
+
+use crate::config::{Onebot, OnebotMode, ProxyConfig};
+use crate::model::{Address, ChatPlatform, MessageIn, ReplyRef, SenderProfile, Trigger};
+use crate::platform::{InSink, PlatformHealth};
+
+use super::message::{
+    decode_attachments, decode_mentions, decode_reply, decode_text, segments_of, starts_with_at,
+};
+use super::server;
+
+/// 比较全部字节后才给出结果，耗时不随第一个不同字节的位置变化，避免逐字节猜出 token
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+type WsStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+pub struct OnebotStack {
+    pub(crate) cfg: Onebot,
+
+    sink: RwLock<Option<InSink>>,
+    started: AtomicBool,
+    outbound: Mutex<Option<mpsc::UnboundedSender<WsMessage>>>,
+    pending: Mutex<HashMap<String, oneshot::Sender<Value>>>,
+    echo_seq: AtomicU64,
+    http: reqwest::Client,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl OnebotStack {
+    const API_TIMEOUT: Duration = Duration::from_secs(10);
+    const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+    pub fn new(cfg: Onebot, proxy: &ProxyConfig) -> Result<Arc<Self>> {
+        let builder = reqwest::Client::builder().timeout(Self::API_TIMEOUT);
+        let http = proxy
+            .apply_to_reqwest_builder(builder)?
+            .build()
+            .context("build onebot http client")?;
+
+        Ok(Arc::new(Self {
+            cfg,
+            sink: RwLock::new(None),
+            started: AtomicBool::new(false),
+            outbound: Mutex::new(None),
+            pending: Mutex::new(HashMap::new()),
+            echo_seq: AtomicU64::new(0),
+            http,
+            task: Mutex::new(None),
+        }))
+    }
+
+    pub async fn set_sink(&self, sink: InSink) {
+        *self.sink.write().await = Some(sink);
+    }
+
+    pub async fn ensure_started(self: &Arc<Self>) -> Result<()> {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let res = match self.cfg.mode {
+            OnebotMode::ForwardWs => self.start_forward_ws().await,
+            OnebotMode::ReverseWs => server::serve_reverse_ws(self.clone()).await,
+            OnebotMode::HttpPost => server::serve_http_post(self.clone()).await,
+        };
+
+        match res {
+            Ok(jh) => {
+                *self.task.lock().unwrap() = Some(jh);
+                Ok(())
+            }
+            Err(e) => {
+                self.started.store(false, Ordering::SeqCst);
+                Err(e)
+            }
+        }
+    }
+
+    pub fn shutdown(&self) {
+        if let Some(jh) = self.task.lock().unwrap().take() {
+            jh.abort();
+        }
+        // 丢弃发送端，写任务随之退出
+        *self.outbound.lock().unwrap() = None;
+        self.started.store(false, Ordering::SeqCst);
+    }
+
+    pub fn health(&self) -> PlatformHealth {
+        if !self.started.load(Ordering::SeqCst) {
+            return PlatformHealth::Stopped;
+        }
+        // HTTP POST 模式无长连接，只要监听任务还在即视为正常
+        let alive = match self.cfg.mode {
+            OnebotMode::HttpPost => self
+                .task
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|jh| !jh.is_finished()),
+            OnebotMode::ForwardWs | OnebotMode::ReverseWs => {
+                self.outbound.lock().unwrap().is_some()
+            }
+        };
+        if alive {
+            PlatformHealth::Running
+        } else {
+            PlatformHealth::Disconnected
+        }
+    }
+
+    /// 校验 `Authorization: Bearer <token>` 或 `?access_token=`；未配置 token 时放行。
+    pub(crate) fn authorized(&self, auth_header: Option<&str>, query: Option<&str>) -> bool {
+        let token = self.cfg.access_token.trim();
+        if token.is_empty() {
+            return true;
+        }
+
+        let by_header = auth_header
+            .and_then(|h| {
+                h.strip_prefix("Bearer ")
+                    .or_else(|| h.strip_prefix("Token "))
+            })
+            .is_some_and(|t| constant_time_eq(t.trim().as_bytes(), token.as_bytes()));
+
+        let by_query = query.is_some_and(|q| {
+            url::form_urlencoded::parse(q.as_bytes()).any(|(k, v)| {
+                k == "access_token" && constant_time_eq(v.as_bytes(), token.as_bytes())
+            })
+        });
+
+        by_header || by_query
+    }
+
+    async fn start_forward_ws(self: &Arc<Self>) -> Result<JoinHandle<()>> {
+        // 首次连接失败直接报错，之后断线由后台任务负责重连
+        let ws = self.connect().await?;
+
+        let stack = self.clone();
+        Ok(tokio::spawn(async move {
+            let mut ws = Some(ws);
+            loop {
+                if let Some(conn) = ws.take() {
+                    stack.run_session(conn).await;
+                }
+                warn!(
+                    "onebot ws disconnected, reconnecting in {:?}",
+                    Self::RECONNECT_DELAY
+                );
+                tokio::time::sleep(Self::RECONNECT_DELAY).await;
+                match stack.connect().await {
+                    Ok(conn) => ws = Some(conn),
+                    Err(e) => error!("onebot ws reconnect failed: {e:?}"),
+                }
+            }
+        }))
+    }
+
+    async fn connect(&self) -> Result<WsStream> {
+        let mut req = self
+            .cfg
+            .ws_url
+            .as_str()
+            .into_client_request()
+            .context("invalid onebot ws-url")?;
+
+        let token = self.cfg.access_token.trim();
+        if !token.is_empty() {
+            req.headers_mut().insert(
+                "Authorization",
+                HeaderValue::from_str(&format!("Bearer {token}"))?,
+            );
+        }
+
+        let (ws, _resp) = tokio_tungstenite::connect_async(req)
+            .await
+            .with_context(|| format!("connect onebot ws {}", self.cfg.ws_url))?;
+        info!("onebot ws connected: {}", self.cfg.ws_url);
+        Ok(ws)
+    }
+
+    /// 正向 / 反向 WS 共用的收发循环；反向 WS 下新连接会顶替旧连接成为 API 出口。
+    pub(crate) async fn run_session<S>(&self, ws: WebSocketStream<S>)
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (mut write, mut read) = ws.split();
+        let (tx, mut rx) = mpsc::unbounded_channel::<WsMessage>();
+        *self.outbound.lock().unwrap() = Some(tx.clone());
+
+        let writer = tokio::spawn(async move {
+            while let Some(m) = rx.recv().await {
+                if let Err(e) = write.send(m).await {
+                    error!("onebot ws write failed: {e:?}");
+                    break;
+                }
+            }
+        });
+
+        while let Some(frame) = read.next().await {
+            match frame {
+                Ok(WsMessage::Text(text)) => self.on_frame(text.as_str()).await,
+                Ok(WsMessage::Ping(p)) => {
+                    let _ = tx.send(WsMessage::Pong(p));
+                }
+                Ok(WsMessage::Close(_)) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    error!("onebot ws read failed: {e:?}");
+                    break;
+                }
+            }
+        }
+
+        {
+            let mut out = self.outbound.lock().unwrap();
+            if out.as_ref().is_some_and(|o| o.same_channel(&tx)) {
+                *out = None;
+                // 断线后未返回的 API 调用不会再有响应
+                self.pending.lock().unwrap().clear();
+            }
+        }
+        writer.abort();
+    }
+
+    async fn on_frame(&self, text: &str) {
+        let v: Value = match serde_json::from_str(text) {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("onebot bad frame: {e} text={text}");
+                return;
+            }
+        };
+
+        // API 响应：带 echo，无 post_type
+        if v.get("post_type").is_none() {
+            if let Some(echo) = v.get("echo").and_then(echo_key)
+                && let Some(tx) = self.pending.lock().unwrap().remove(&echo)
+            {
+                let _ = tx.send(v);
+            }
+            return;
+        }
+
+        self.on_event(&v).await;
+    }
+
+    pub(crate) async fn on_event(&self, v: &Value) {
+        match v["post_type"].as_str() {
+            Some("message") => {}
+            Some("meta_event") => {
+                debug!("onebot meta_event: {}", v["meta_event_type"]);
+                return;
+            }
+            _ => return,
+        }
+
+        // 频道消息的 user_id 为字符串 tiny_id
+        let user_id = match &v["user_id"] {
+            Value::Number(n) => n.as_i64(),
+            Value::String(s) => s.parse().ok(),
+            _ => None,
+        };
+
+        let addr = match v["message_type"].as_str() {
+            Some("group") => match v["group_id"].as_i64() {
+                Some(g) => Address::new(ChatPlatform::Onebot, g, true),
+                None => return,
+            },
+            Some("private") => match user_id {
+                Some(u) => Address::new(ChatPlatform::Onebot, u, false),
+                None => return,
+            },
+            Some("guild") => match (id_string(&v["guild_id"]), id_string(&v["channel_id"])) {
+                (Some(g), Some(c)) => Address::new(ChatPlatform::Onebot, c, true).with_guild(g),
+                _ => return,
+            },
+            _ => return,
+        };
+
+        let segs = segments_of(&v["message"]);
+        let text = decode_text(&segs);
+        let attachments = decode_attachments(&segs);
+        if text.trim().is_empty() && attachments.is_empty() {
+            return;
+        }
+
+        let mut input = MessageIn::new(addr, user_id, text.trim().to_string());
+        input.message_id = id_string(&v["message_id"]).map(|id| {
+            id.parse::<i64>()
+                .map_or_else(|_| id.clone().into(), Into::into)
+        });
+        input.timestamp = v["time"].as_i64();
+        input.attachments = attachments;
+        input.mentions = decode_mentions(&segs);
+        input.reply_to = decode_reply(&segs).map(|message_id| ReplyRef {
+            message_id,
+            user_id: None,
+            text: None,
+        });
+
+        let self_id = v["self_id"].as_i64();
+        input.mentioned_bot = self_id
+            .is_some_and(|me| input.mentions.iter().any(|m| m.user_id == Some(me)));
+        // `at` 段不计入文本，`[CQ:at,qq=bot] help` 的文本已是 `help`
+        if self_id.is_some_and(|me| starts_with_at(&segs, me)) && !input.text.is_empty() {
+            input.trigger = Trigger::Mention;
+        }
+
+        let sender = &v["sender"];
+        input.sender = SenderProfile {
+            // 群名片优先于昵称
+            display_name: sender["card"]
+                .as_str()
+                .filter(|c| !c.is_empty())
+                .or(sender["nickname"].as_str())
+                .map(str::to_string),
+            username: sender["nickname"].as_str().map(str::to_string),
+            language: None,
+            is_bot: false,
+            // 群消息自带 owner / admin / member
+            is_chat_admin: sender["role"]
+                .as_str()
+                .map(|r| r == "owner" || r == "admin"),
+        };
+
+        if let Some(sink) = self.sink.read().await.as_ref() {
+            let _ = sink.send(input);
+        }
+    }
+
+    /// 调用 OneBot API：WS 模式按 echo 等待响应，HTTP 模式直接请求 `api-url/<action>`。
+    pub async fn call_api(&self, action: &str, params: Value) -> Result<Value> {
+        let resp = match self.cfg.mode {
+            OnebotMode::HttpPost => self.call_http(action, params).await?,
+            OnebotMode::ForwardWs | OnebotMode::ReverseWs => self.call_ws(action, params).await?,
+        };
+
+        if resp["status"] == "failed" || resp["retcode"].as_i64().unwrap_or(0) != 0 {
+            return Err(anyhow!(
+                "onebot {action} failed: retcode={} msg={}",
+                resp["retcode"],
+                resp.get("wording")
+                    .or(resp.get("msg"))
+                    .unwrap_or(&Value::Null)
+            ));
+        }
+
+        Ok(resp["data"].clone())
+    }
+
+    async fn call_ws(&self, action: &str, params: Value) -> Result<Value> {
+        let echo = self.echo_seq.fetch_add(1, Ordering::Relaxed).to_string();
+        let req = json!({ "action": action, "params": params, "echo": echo });
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(echo.clone(), tx);
+
+        let sent = match self.outbound.lock().unwrap().as_ref() {
+            Some(out) => out.send(WsMessage::Text(req.to_string().into())).is_ok(),
+            None => false,
+        };
+        if !sent {
+            self.pending.lock().unwrap().remove(&echo);
+            return Err(anyhow!("onebot ws not connected"));
+        }
+
+        match tokio::time::timeout(Self::API_TIMEOUT, rx).await {
+            Ok(Ok(v)) => Ok(v),
+            Ok(Err(_)) => Err(anyhow!("onebot connection lost during {action}")),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&echo);
+                Err(anyhow!("onebot {action} timed out"))
+            }
+        }
+    }
+
+    async fn call_http(&self, action: &str, params: Value) -> Result<Value> {
+        let url = format!("{}/{action}", self.cfg.api_url.trim_end_matches('/'));
+
+        let mut req = self
+            .http
+            .post(&url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(params.to_string());
+        let token = self.cfg.access_token.trim();
+        if !token.is_empty() {
+            req = req.bearer_auth(token);
+        }
+
+        let resp = req
+            .send()
+            .await
+            .with_context(|| format!("onebot http api {url}"))?;
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(anyhow!("onebot {action} http status {status}"));
+        }
+
+        let body = resp.text().await?;
+        serde_json::from_str(&body).map_err(|e| anyhow!("bad json: {e}"))
+    }
+}
+
+fn echo_key(v: &Value) -> Option<String> {
+    match v {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn id_string(v: &Value) -> Option<String> {
+    match v {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::message_sender_hub::Sender;
+    use crate::model::{ChatId, MessageOut};
+    use crate::platform::onebot::sender::OnebotSender;
+    use tokio::net::TcpListener;
+
+    const SELF_ID: i64 = 10001;
+
+    #[test]
+    fn access_token_checks() {
+        let stack = OnebotStack::new(
+            Onebot {
+                access_token: "secret-token".to_string(),
+                ..Default::default()
+            },
+            &ProxyConfig::default(),
+        )
+        .unwrap();
+        assert!(stack.authorized(Some("Bearer secret-token"), None));
+        assert!(stack.authorized(Some("Token secret-token"), None));
+        assert!(stack.authorized(None, Some("a=1&access_token=secret-token")));
+        assert!(!stack.authorized(Some("Bearer secret"), None));
+        assert!(!stack.authorized(Some("Bearer secret-token2"), None));
+        assert!(!stack.authorized(Some("secret-token"), None));
+        assert!(!stack.authorized(None, Some("access_token=Secret-token")));
+        assert!(!stack.authorized(None, None));
+
+        let open = OnebotStack::new(Onebot::default(), &ProxyConfig::default()).unwrap();
+        assert!(open.authorized(None, None));
+    }
+
+    /// 本地模拟的 OneBot 实现：接受一个正向 WS 连接，校验 token 后推送一条群消息，
+    /// 再应答 bot 发来的 `send_group_msg`
+    #[tokio::test]
+    async fn forward_ws_against_mock_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            #[allow(clippy::result_large_err)]
+            let check = |req: &tokio_tungstenite::tungstenite::handshake::server::Request,
+                         resp| {
+                assert_eq!(req.headers()["Authorization"], "Bearer secret-token");
+                Ok(resp)
+            };
+            let mut ws = tokio_tungstenite::accept_hdr_async(tcp, check).await.unwrap();
+
+            let event = json!({
+                "post_type": "message",
+                "message_type": "group",
+                "time": 1700000000,
+                "self_id": SELF_ID,
+                "group_id": 123456,
+                "user_id": 42,
+                "message_id": 7,
+                "message": format!("[CQ:at,qq={SELF_ID}] help"),
+                "sender": { "nickname": "alice", "card": "", "role": "admin" },
+            });
+            ws.send(WsMessage::Text(event.to_string().into())).await.unwrap();
+
+            let req = loop {
+                match ws.next().await.unwrap().unwrap() {
+                    WsMessage::Text(t) => break serde_json::from_str::<Value>(t.as_str()).unwrap(),
+                    _ => continue,
+                }
+            };
+            let resp = json!({
+                "status": "ok",
+                "retcode": 0,
+                "data": { "message_id": 99 },
+                "echo": req["echo"],
+            });
+            ws.send(WsMessage::Text(resp.to_string().into())).await.unwrap();
+            req
+        });
+
+        let cfg = Onebot {
+            enabled: true,
+            ws_url: format!("ws://127.0.0.1:{port}"),
+            access_token: "secret-token".to_string(),
+            ..Default::default()
+        };
+        let stack = OnebotStack::new(cfg, &ProxyConfig::default()).unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        stack.set_sink(tx).await;
+        stack.ensure_started().await.unwrap();
+
+        let input = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(input.addr.key(), Address::new(ChatPlatform::Onebot, 123456i64, true).key());
+        assert_eq!(input.user_id, Some(42));
+        assert_eq!(input.text, "help");
+        assert_eq!(input.trigger, Trigger::Mention);
+        assert!(input.mentioned_bot);
+        assert_eq!(input.sender.display_name.as_deref(), Some("alice"));
+        assert_eq!(input.sender.is_chat_admin, Some(true));
+
+        let sender = OnebotSender::new(stack.clone());
+        let sent = sender
+            .send(MessageOut::text(input.addr.clone(), "pong"))
+            .await
+            .unwrap();
+        assert_eq!(sent, Some(ChatId::Int(99)));
+
+        let req = server.await.unwrap();
+        assert_eq!(req["action"], "send_group_msg");
+        assert_eq!(req["params"]["group_id"], 123456);
+        assert_eq!(
+            req["params"]["message"],
+            json!([{ "type": "text", "data": { "text": "pong" } }])
+        );
+        stack.shutdown();
+    }
+}
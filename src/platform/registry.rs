@@ -0,0 +1,94 @@
+// src/platform/registry.rs
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, error, info, warn};
+
+use crate::config::AppProperties;
+use crate::core::{CommandRegistry, MessageSenderHub};
+
+use super::adapter::{PlatformAdapter, PlatformHealth};
+use super::{InSink, discord, onebot, telegram};
+
+/// 按配置构造适配器；平台未启用时返回 `None`，配置有误时返回错误。
+type AdapterFactory = fn(&AppProperties) -> Result<Option<Arc<dyn PlatformAdapter>>>;
+
+const FACTORIES: &[AdapterFactory] = &[
+    telegram::from_props,
+    discord::from_props,
+    onebot::from_props,
+];
+
+pub struct PlatformRegistry {
+    adapters: Vec<Arc<dyn PlatformAdapter>>,
+}
+
+impl PlatformRegistry {
+    pub fn build(props: &AppProperties) -> Result<Self> {
+        let mut adapters = Vec::new();
+        for f in FACTORIES {
+            adapters.extend(f(props)?);
+        }
+        Ok(Self { adapters })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adapters.is_empty()
+    }
+
+    pub fn list_platforms(&self) -> String {
+        self.adapters
+            .iter()
+            .map(|a| a.name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// bind -> start -> 注册 sender，任一平台失败即中止启动
+    pub async fn start_all(&self, sink: InSink, hub: &MessageSenderHub) -> Result<()> {
+        for a in &self.adapters {
+            let t0 = Instant::now();
+            info!("starting {}...", a.name());
+
+            a.bind(sink.clone()).await;
+            debug!("{} bind done", a.name());
+
+            a.start()
+                .await
+                .with_context(|| format!("{}.start failed", a.name()))?;
+            info!("{} started in {:?}", a.name(), t0.elapsed());
+
+            hub.register(a.platform(), a.sender().await?);
+            debug!("{:?} sender registered into hub", a.platform());
+        }
+        Ok(())
+    }
+
+    /// 同步原生命令；失败只记录日志，不影响文本命令
+    pub async fn sync_commands(&self, registry: &CommandRegistry) {
+        for a in &self.adapters {
+            match a.sync_commands(registry).await {
+                Ok(()) => debug!("{} commands synced", a.name()),
+                Err(e) => warn!("{} sync commands failed: {e:?}", a.name()),
+            }
+        }
+    }
+
+    /// 按启动的逆序关闭
+    pub async fn shutdown_all(&self) {
+        for a in self.adapters.iter().rev() {
+            info!("shutting down {}...", a.name());
+            let jh = tokio::spawn({
+                let a = a.clone();
+                async move { a.shutdown().await }
+            });
+            if let Err(e) = jh.await {
+                error!("{} shutdown failed: {e:?}", a.name());
+            }
+        }
+    }
+
+    pub fn health(&self) -> Vec<(&'static str, PlatformHealth)> {
+        self.adapters.iter().map(|a| (a.name(), a.health())).collect()
+    }
+}
@@ -1,5 +1,19 @@
 // src/platform/telegram/mod.rs
 pub mod receiver;
+pub mod render;
 pub mod sender;
 
 pub use receiver::TelegramReceiver;
+
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::config::AppProperties;
+use crate::platform::PlatformAdapter;
+
+pub fn from_props(props: &AppProperties) -> Result<Option<Arc<dyn PlatformAdapter>>> {
+    if !props.telegram.enabled {
+        return Ok(None);
+    }
+    Ok(Some(Arc::new(TelegramReceiver::new(props.telegram.clone()))))
+}
@@ -1,43 +1,67 @@
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use std::convert::Infallible;
 use std::sync::{Arc, Mutex};
 use teloxide::dispatching::DefaultKey;
+use teloxide::types::{
+    BotCommand as TgCommand, BotCommandScope, CallbackQuery, FileMeta, MessageEntityKind, User,
+};
 use teloxide::{dispatching::UpdateFilterExt, prelude::*};
-use tokio::{sync::mpsc, task::JoinHandle};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
 
+use crate::config::{Telegram, TelegramCommandScope};
+use crate::core::CommandRegistry;
 use crate::core::message_sender_hub::Sender;
-use crate::lifecycle::Closeable;
-use crate::model::{Address, ChatPlatform, MessageIn};
+use crate::i18n;
+use crate::model::{
+    Address, ChatPlatform, InAttachment, InContentType, Mention, MessageIn, ReplyRef,
+    SenderProfile, Trigger,
+};
+use crate::platform::{InSink, PlatformAdapter, PlatformHealth, strip_mention};
 
 use super::sender::TelegramSender;
 
-pub type InSink = mpsc::UnboundedSender<MessageIn>;
-
 struct TelegramStack {
     bot: Bot,
+    bot_username: String,
 }
 
 pub struct TelegramReceiver {
     stack: Arc<TelegramStack>,
+    cfg: Telegram,
     sink: Arc<Mutex<Option<InSink>>>,
     task: Arc<Mutex<Option<JoinHandle<()>>>>,
 }
 
 impl TelegramReceiver {
-    pub fn new(token: String) -> Self {
-        let bot = Bot::new(token);
+    pub fn new(cfg: Telegram) -> Self {
+        let bot = Bot::new(cfg.bot_token.clone());
+        let bot_username = cfg.bot_username.trim().trim_start_matches('@').to_string();
         Self {
-            stack: Arc::new(TelegramStack { bot }),
+            stack: Arc::new(TelegramStack { bot, bot_username }),
+            cfg,
             sink: Arc::new(Mutex::new(None)),
             task: Arc::new(Mutex::new(None)),
         }
     }
+}
+
+#[async_trait]
+impl PlatformAdapter for TelegramReceiver {
+    fn name(&self) -> &'static str {
+        "TelegramReceiver"
+    }
+
+    fn platform(&self) -> ChatPlatform {
+        ChatPlatform::Telegram
+    }
 
-    pub async fn bind(&self, sink: InSink) {
+    async fn bind(&self, sink: InSink) {
         *self.sink.lock().unwrap() = Some(sink);
     }
 
-    pub async fn start(&self) -> Result<()> {
+    async fn start(&self) -> Result<()> {
         if self.task.lock().unwrap().is_some() {
             return Ok(());
         }
@@ -51,27 +75,46 @@ impl TelegramReceiver {
 
         let bot = self.stack.bot.clone();
 
-        let handler = teloxide::dptree::entry().branch(Update::filter_message().endpoint(
-            move |msg: Message| {
+        // 未配置用户名时从 getMe 获取，用于识别 `/cmd@botname` 与 @ 提及
+        let bot_username = if self.stack.bot_username.is_empty() {
+            match bot.get_me().await {
+                Ok(me) => me.username().to_string(),
+                Err(e) => {
+                    warn!("telegram getMe failed, bot username unknown: {e:?}");
+                    String::new()
+                }
+            }
+        } else {
+            self.stack.bot_username.clone()
+        };
+
+        let callback_sink = sink.clone();
+        let handler = teloxide::dptree::entry()
+            .branch(Update::filter_message().endpoint(move |msg: Message| {
                 let sink = sink.clone();
+                let bot_username = bot_username.clone();
                 async move {
-                    if let Some(text) = msg.text() {
-                        let chat_id = msg.chat.id.0;
-                        let is_group = msg.chat.is_group() || msg.chat.is_supergroup();
-
-                        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64);
-
-                        let _ = sink.send(MessageIn::new(
-                            Address::new(ChatPlatform::Telegram, chat_id, is_group),
-                            user_id,
-                            text.to_string(),
-                        ));
+                    if let Some(input) = to_message_in(&msg, &bot_username) {
+                        let _ = sink.send(input);
                     }
 
                     Ok::<(), Infallible>(())
                 }
-            },
-        ));
+            }))
+            .branch(Update::filter_callback_query().endpoint(
+                move |bot: Bot, q: CallbackQuery| {
+                    let sink = callback_sink.clone();
+                    async move {
+                        // 先应答，否则客户端按钮会一直转圈
+                        let _ = bot.answer_callback_query(q.id.clone()).await;
+                        if let Some(input) = callback_in(&q) {
+                            let _ = sink.send(input);
+                        }
+
+                        Ok::<(), Infallible>(())
+                    }
+                },
+            ));
 
         let mut dispatcher =
             Dispatcher::<Bot, Infallible, DefaultKey>::builder(bot, handler).build();
@@ -84,16 +127,302 @@ impl TelegramReceiver {
         Ok(())
     }
 
-    pub async fn sender(&self) -> Result<Arc<dyn Sender>> {
+    async fn sender(&self) -> Result<Arc<dyn Sender>> {
         self.start().await?;
         Ok(Arc::new(TelegramSender::new(self.stack.bot.clone())))
     }
-}
 
-impl Closeable for TelegramReceiver {
-    fn close(&self) {
-        if let Some(jh) = self.task.lock().unwrap().take() {
+    /// 每个作用范围发布一份平台默认语言的菜单，以及每种内置语言按 `language_code` 各一份
+    async fn sync_commands(&self, registry: &CommandRegistry) -> Result<()> {
+        if !self.cfg.sync_commands {
+            return Ok(());
+        }
+
+        let menu = |lang: &str| -> Vec<TgCommand> {
+            registry
+                .all()
+                .iter()
+                .filter(|c| c.visible() && is_menu_name(c.name()))
+                .map(|c| {
+                    let desc: String = c.description(lang).chars().take(256).collect();
+                    TgCommand::new(c.name(), desc)
+                })
+                .collect()
+        };
+        let default = menu(registry.settings().platform_language(ChatPlatform::Telegram));
+
+        // Telegram 的 language_code 为两位 ISO 639-1，如 zh-cn -> zh；同语种取第一个目录
+        let mut localized: Vec<(String, Vec<TgCommand>)> = vec![];
+        for locale in i18n::locales() {
+            let code = locale.split('-').next().unwrap_or(locale).to_string();
+            if !localized.iter().any(|(c, _)| *c == code) {
+                localized.push((code, menu(locale)));
+            }
+        }
+
+        for scope in &self.cfg.command_scopes {
+            let scope = match scope {
+                TelegramCommandScope::Default => BotCommandScope::Default,
+                TelegramCommandScope::Private => BotCommandScope::AllPrivateChats,
+                TelegramCommandScope::Group => BotCommandScope::AllGroupChats,
+            };
+
+            self.stack
+                .bot
+                .set_my_commands(default.clone())
+                .scope(scope.clone())
+                .await?;
+            for (code, cmds) in &localized {
+                self.stack
+                    .bot
+                    .set_my_commands(cmds.clone())
+                    .scope(scope.clone())
+                    .language_code(code.clone())
+                    .await?;
+            }
+        }
+
+        let codes: Vec<&str> = localized.iter().map(|(c, _)| c.as_str()).collect();
+        info!(
+            "telegram: {} commands synced (scopes: {:?}, languages: {codes:?})",
+            default.len(),
+            self.cfg.command_scopes
+        );
+        Ok(())
+    }
+
+    async fn shutdown(&self) {
+        let jh = self.task.lock().unwrap().take();
+        if let Some(jh) = jh {
             jh.abort();
+            let _ = jh.await;
+        }
+    }
+
+    fn health(&self) -> PlatformHealth {
+        match self.task.lock().unwrap().as_ref() {
+            None => PlatformHealth::Stopped,
+            Some(jh) if jh.is_finished() => PlatformHealth::Disconnected,
+            Some(_) => PlatformHealth::Running,
+        }
+    }
+}
+
+/// 群组中 Telegram 会发送 `/cmd@botname`：指向本 bot 时去掉 `/` 和后缀，指向其他 bot 时整条忽略。
+/// 以 `@botname` 开头的消息去掉提及，交给核心按 `mention-prefix` 判断。
+fn command_text(text: &str, bot_username: &str) -> Option<(String, Trigger)> {
+    let t = text.trim_start();
+    if let Some(first) = t.split_whitespace().next()
+        && let Some(cmd) = first.strip_prefix('/')
+        && let Some((cmd, target)) = cmd.split_once('@')
+    {
+        if !bot_username.is_empty() && !target.eq_ignore_ascii_case(bot_username) {
+            return None;
+        }
+        return Some((format!("{cmd}{}", &t[first.len()..]), Trigger::Explicit));
+    }
+
+    let mention = format!("@{bot_username}");
+    if !bot_username.is_empty()
+        && let Some(rest) = strip_mention(t, &[&mention])
+    {
+        return Some((rest.to_string(), Trigger::Mention));
+    }
+    Some((text.to_string(), Trigger::None))
+}
+
+/// 菜单命令名只允许 1-32 位小写字母、数字和下划线
+fn is_menu_name(name: &str) -> bool {
+    (1..=32).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// 文本取 `text` 或图片 / 文件的 `caption`；既无文本也无附件的消息（入群、置顶等）忽略
+fn to_message_in(msg: &Message, bot_username: &str) -> Option<MessageIn> {
+    let (text, trigger) = command_text(msg.text().or(msg.caption()).unwrap_or(""), bot_username)?;
+    let attachments = attachments_of(msg);
+    if text.is_empty() && attachments.is_empty() {
+        return None;
+    }
+
+    let user_id = msg.from.as_ref().map(|u| u.id.0 as i64);
+    let mut input = MessageIn::new(addr_of(msg), user_id, text);
+
+    input.message_id = Some((msg.id.0 as i64).into());
+    input.timestamp = Some(msg.date.timestamp());
+    input.attachments = attachments;
+    input.trigger = trigger;
+    input.sender = msg.from.as_ref().map(profile_of).unwrap_or_default();
+
+    let entities = msg
+        .parse_entities()
+        .or_else(|| msg.parse_caption_entities())
+        .unwrap_or_default();
+    for e in entities {
+        match e.kind() {
+            MessageEntityKind::Mention => {
+                let name = e.text().trim_start_matches('@');
+                if !bot_username.is_empty() && name.eq_ignore_ascii_case(bot_username) {
+                    input.mentioned_bot = true;
+                }
+                input.mentions.push(Mention {
+                    user_id: None,
+                    username: Some(name.to_string()),
+                });
+            }
+            MessageEntityKind::TextMention { user } => {
+                input.mentions.push(Mention {
+                    user_id: Some(user.id.0 as i64),
+                    username: user.username.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(r) = msg.reply_to_message() {
+        let from_bot = r.from.as_ref().is_some_and(|u| {
+            u.is_bot
+                && u.username
+                    .as_deref()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(bot_username))
+        });
+        if from_bot {
+            input.mentioned_bot = true;
+        }
+        input.reply_to = Some(ReplyRef {
+            message_id: (r.id.0 as i64).into(),
+            user_id: r.from.as_ref().map(|u| u.id.0 as i64),
+            text: r.text().or(r.caption()).map(str::to_string),
+        });
+    }
+
+    Some(input)
+}
+
+/// 内联按钮回调；按钮所在消息已不可访问（过旧）时忽略
+fn callback_in(q: &CallbackQuery) -> Option<MessageIn> {
+    let msg = q.regular_message()?;
+    let mut input = MessageIn::new(addr_of(msg), Some(q.from.id.0 as i64), String::new());
+    input.message_id = Some((msg.id.0 as i64).into());
+    input.sender = profile_of(&q.from);
+    input.callback = q.data.clone();
+    Some(input)
+}
+
+fn addr_of(msg: &Message) -> Address {
+    let is_group = msg.chat.is_group() || msg.chat.is_supergroup();
+    let addr = Address::new(ChatPlatform::Telegram, msg.chat.id.0, is_group);
+    match msg.thread_id {
+        Some(thread) if msg.is_topic_message => addr.with_thread(thread.0.0 as i64),
+        _ => addr,
+    }
+}
+
+fn profile_of(u: &User) -> SenderProfile {
+    SenderProfile {
+        display_name: Some(u.full_name()),
+        username: u.username.clone(),
+        language: u.language_code.clone(),
+        is_bot: u.is_bot,
+        is_chat_admin: None,
+    }
+}
+
+fn attachments_of(msg: &Message) -> Vec<InAttachment> {
+    fn att(
+        ty: InContentType,
+        file: &FileMeta,
+        name: Option<&String>,
+        mime: Option<String>,
+    ) -> InAttachment {
+        InAttachment {
+            ty,
+            file_id: file.id.0.clone(),
+            name: name.cloned(),
+            mime,
+            size: Some(file.size as u64),
+            url: None,
         }
     }
+
+    let mut out = vec![];
+    // 同一张图有多种尺寸，取最大的一张
+    if let Some(p) = msg.photo().and_then(|p| p.last()) {
+        out.push(att(InContentType::Photo, &p.file, None, None));
+    }
+    if let Some(d) = msg.document() {
+        let mime = d.mime_type.as_ref().map(|m| m.to_string());
+        out.push(att(InContentType::Document, &d.file, d.file_name.as_ref(), mime));
+    }
+    if let Some(s) = msg.sticker() {
+        out.push(att(InContentType::Sticker, &s.file, None, None));
+    }
+    if let Some(a) = msg.audio() {
+        let mime = a.mime_type.as_ref().map(|m| m.to_string());
+        out.push(att(InContentType::Audio, &a.file, a.file_name.as_ref(), mime));
+    }
+    if let Some(v) = msg.video() {
+        let mime = v.mime_type.as_ref().map(|m| m.to_string());
+        out.push(att(InContentType::Video, &v.file, v.file_name.as_ref(), mime));
+    }
+    if let Some(v) = msg.voice() {
+        let mime = v.mime_type.as_ref().map(|m| m.to_string());
+        out.push(att(InContentType::Voice, &v.file, None, mime));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_addressed_to_this_bot() {
+        assert_eq!(
+            command_text("/help@LukosBot switch", "lukosbot"),
+            Some(("help switch".to_string(), Trigger::Explicit))
+        );
+        assert_eq!(
+            command_text("/ping@lukosbot", "lukosbot"),
+            Some(("ping".to_string(), Trigger::Explicit))
+        );
+    }
+
+    #[test]
+    fn command_addressed_to_other_bot_is_ignored() {
+        assert_eq!(command_text("/help@otherbot", "lukosbot"), None);
+        assert_eq!(command_text("/help@otherbot arg", "lukosbot"), None);
+    }
+
+    #[test]
+    fn leading_mention_is_stripped() {
+        assert_eq!(
+            command_text("@lukosbot ping now", "lukosbot"),
+            Some(("ping now".to_string(), Trigger::Mention))
+        );
+        assert_eq!(
+            command_text("@lukosbotx ping", "lukosbot"),
+            Some(("@lukosbotx ping".to_string(), Trigger::None))
+        );
+        assert_eq!(
+            command_text("/ping", "lukosbot"),
+            Some(("/ping".to_string(), Trigger::None))
+        );
+    }
+
+    /// 还没拿到 bot 用户名时不判断 `@` 指向谁，也不识别提及
+    #[test]
+    fn empty_bot_username() {
+        assert_eq!(
+            command_text("/help@anybot arg", ""),
+            Some(("help arg".to_string(), Trigger::Explicit))
+        );
+        assert_eq!(
+            command_text("@ ping", ""),
+            Some(("@ ping".to_string(), Trigger::None))
+        );
+    }
 }
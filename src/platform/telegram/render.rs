@@ -0,0 +1,100 @@
+// src/platform/telegram/render.rs
+use crate::model::Segment;
+
+/// 渲染为 Telegram `ParseMode::Html`；只有 `<`、`>`、`&`、`"` 需要转义
+pub fn render_html(segs: &[Segment]) -> String {
+    let mut sb = String::new();
+    for s in segs {
+        match s {
+            Segment::Text(t) => sb.push_str(&escape(t)),
+            Segment::Bold(t) => sb.push_str(&format!("<b>{}</b>", escape(t))),
+            Segment::Italic(t) => sb.push_str(&format!("<i>{}</i>", escape(t))),
+            Segment::Code(t) => sb.push_str(&format!("<code>{}</code>", escape(t))),
+            Segment::CodeBlock { lang, code } => match lang {
+                Some(l) => sb.push_str(&format!(
+                    "<pre><code class=\"language-{}\">{}</code></pre>",
+                    escape(l),
+                    escape(code)
+                )),
+                None => sb.push_str(&format!("<pre>{}</pre>", escape(code))),
+            },
+            Segment::Link { text, url } => {
+                sb.push_str(&format!("<a href=\"{}\">{}</a>", escape(url), escape(text)))
+            }
+            Segment::MentionUser { user_id, name } => {
+                let name = name.clone().unwrap_or_else(|| user_id.to_string());
+                sb.push_str(&format!(
+                    "<a href=\"tg://user?id={user_id}\">@{}</a>",
+                    escape(&name)
+                ));
+            }
+            Segment::Quote(t) => sb.push_str(&format!("<blockquote>{}</blockquote>", escape(t))),
+            Segment::Attachment(_) => {}
+            Segment::LineBreak => sb.push('\n'),
+        }
+    }
+    sb
+}
+
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_html_in_every_segment() {
+        let segs = vec![
+            Segment::text("a<b>&\"c\" "),
+            Segment::Bold("<x>".into()),
+            Segment::Italic("i&j".into()),
+            Segment::Code("<tag>".into()),
+            Segment::LineBreak,
+            Segment::Link {
+                text: "t<>".into(),
+                url: "https://x/?a=1&b=\"2\"".into(),
+            },
+        ];
+        assert_eq!(
+            render_html(&segs),
+            "a&lt;b&gt;&amp;&quot;c&quot; <b>&lt;x&gt;</b><i>i&amp;j</i><code>&lt;tag&gt;</code>\n\
+             <a href=\"https://x/?a=1&amp;b=&quot;2&quot;\">t&lt;&gt;</a>"
+        );
+    }
+
+    #[test]
+    fn code_blocks_quotes_and_mentions() {
+        let segs = vec![
+            Segment::CodeBlock {
+                lang: Some("rust".into()),
+                code: "a < b && c".into(),
+            },
+            Segment::CodeBlock {
+                lang: None,
+                code: "</pre>".into(),
+            },
+            Segment::Quote("x > y".into()),
+            Segment::MentionUser {
+                user_id: 42,
+                name: Some("<bob>".into()),
+            },
+            Segment::MentionUser {
+                user_id: 7,
+                name: None,
+            },
+        ];
+        assert_eq!(
+            render_html(&segs),
+            "<pre><code class=\"language-rust\">a &lt; b &amp;&amp; c</code></pre>\
+             <pre>&lt;/pre&gt;</pre>\
+             <blockquote>x &gt; y</blockquote>\
+             <a href=\"tg://user?id=42\">@&lt;bob&gt;</a>\
+             <a href=\"tg://user?id=7\">@7</a>"
+        );
+    }
+}
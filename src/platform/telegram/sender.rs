@@ -1,10 +1,19 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use teloxide::{prelude::*, types::InputFile};
+use teloxide::{
+    prelude::*,
+    types::{
+        InlineKeyboardButton, InlineKeyboardMarkup, InputFile, MessageId, ParseMode,
+        ReplyParameters, ThreadId,
+    },
+};
 use url::Url;
 
+use crate::core::chunker;
 use crate::core::message_sender_hub::Sender;
-use crate::model::{MessageOut, OutContentType};
+use crate::model::{self, Button, MessageOut, OutContentType};
+
+use super::render::render_html;
 
 #[derive(Clone)]
 pub struct TelegramSender {
@@ -12,46 +21,142 @@ pub struct TelegramSender {
 }
 
 impl TelegramSender {
+    /// 解析实体后的文本上限（UTF-16 码元）
+    const MAX_TEXT: usize = 4096;
+
     pub fn new(bot: Bot) -> Self {
         Self { bot }
     }
-}
 
-#[async_trait]
-impl Sender for TelegramSender {
-    async fn send(&self, out: MessageOut) -> Result<()> {
-        let chat = ChatId(out.addr.chat_id);
+    /// 返回承载按钮的消息 id：有文本时为文本消息，否则为最后一个附件
+    async fn send_part(&self, out: MessageOut) -> Result<Option<model::ChatId>> {
+        let chat = out
+            .addr
+            .chat_id
+            .as_i64()
+            .map(ChatId)
+            .ok_or_else(|| anyhow!("invalid telegram chat id: {}", out.addr.chat_id))?;
+        let thread = out
+            .addr
+            .thread_id
+            .as_ref()
+            .and_then(|t| t.as_i64())
+            .map(|t| ThreadId(MessageId(t as i32)));
+        // 只挂在第一条实际发出的消息上；原消息已删除时照常发送
+        let mut reply = out
+            .reply_to
+            .as_ref()
+            .and_then(|id| id.as_i64())
+            .map(|id| ReplyParameters::new(MessageId(id as i32)).allow_sending_without_reply());
+
+        let mut text_id = None;
+        let mut last_id = None;
 
-        if let Some(text) = &out.text {
-            if !text.is_empty() {
-                self.bot.send_message(chat, text.clone()).await?;
+        let text = render_html(&out.segments);
+        if !text.trim().is_empty() {
+            let mut req = self
+                .bot
+                .send_message(chat, text)
+                .parse_mode(ParseMode::Html);
+            if let Some(t) = thread {
+                req = req.message_thread_id(t);
+            }
+            if let Some(r) = reply.take() {
+                req = req.reply_parameters(r);
             }
+            if !out.keyboard.is_empty() {
+                req = req.reply_markup(markup_of(&out.keyboard));
+            }
+            text_id = Some(req.await?.id);
         }
 
-        for a in out.attachments {
-            match (a.ty, a.bytes, a.url) {
-                (OutContentType::Image, Some(bytes), _) => {
-                    let f = InputFile::memory((*bytes).clone())
-                        .file_name(a.name.unwrap_or_else(|| "image.bin".into()));
-                    self.bot.send_photo(chat, f).await?;
-                }
-                (OutContentType::Image, None, Some(url)) => {
-                    let f = InputFile::url(Url::parse(&url)?);
-                    self.bot.send_photo(chat, f).await?;
-                }
-                (OutContentType::File, Some(bytes), _) => {
-                    let f = InputFile::memory((*bytes).clone())
-                        .file_name(a.name.unwrap_or_else(|| "file.bin".into()));
-                    self.bot.send_document(chat, f).await?;
+        for a in out.attachments().cloned() {
+            let f = match (&a.bytes, &a.url) {
+                (Some(bytes), _) => InputFile::memory((**bytes).clone()).file_name(a.file_name()),
+                (None, Some(url)) => InputFile::url(Url::parse(url)?),
+                _ => continue,
+            };
+
+            match a.ty {
+                OutContentType::Image => {
+                    let mut req = self.bot.send_photo(chat, f);
+                    if let Some(t) = thread {
+                        req = req.message_thread_id(t);
+                    }
+                    if let Some(r) = reply.take() {
+                        req = req.reply_parameters(r);
+                    }
+                    last_id = Some(req.await?.id);
                 }
-                (OutContentType::File, None, Some(url)) => {
-                    let f = InputFile::url(Url::parse(&url)?);
-                    self.bot.send_document(chat, f).await?;
+                OutContentType::File => {
+                    let mut req = self.bot.send_document(chat, f);
+                    if let Some(t) = thread {
+                        req = req.message_thread_id(t);
+                    }
+                    if let Some(r) = reply.take() {
+                        req = req.reply_parameters(r);
+                    }
+                    last_id = Some(req.await?.id);
                 }
-                _ => {}
             }
         }
 
+        Ok(text_id.or(last_id).map(|id| (id.0 as i64).into()))
+    }
+}
+
+#[async_trait]
+impl Sender for TelegramSender {
+    async fn send(&self, out: MessageOut) -> Result<Option<model::ChatId>> {
+        // 按渲染后的 HTML 计算长度，比 Telegram 的口径略保守
+        let parts = chunker::split(out, Self::MAX_TEXT, |segs| {
+            chunker::utf16_len(&render_html(segs))
+        });
+        let mut last = None;
+        for part in parts {
+            last = self.send_part(part).await?;
+        }
+        Ok(last)
+    }
+
+    async fn edit(&self, message_id: &model::ChatId, out: MessageOut) -> Result<()> {
+        let chat = out
+            .addr
+            .chat_id
+            .as_i64()
+            .map(ChatId)
+            .ok_or_else(|| anyhow!("invalid telegram chat id: {}", out.addr.chat_id))?;
+        let id = message_id
+            .as_i64()
+            .map(|id| MessageId(id as i32))
+            .ok_or_else(|| anyhow!("invalid telegram message id: {message_id}"))?;
+
+        self.bot
+            .edit_message_text(chat, id, render_html(&out.segments))
+            .parse_mode(ParseMode::Html)
+            .reply_markup(markup_of(&out.keyboard))
+            .await?;
         Ok(())
     }
+
+    fn supports_buttons(&self) -> bool {
+        true
+    }
+
+    async fn is_chat_admin(&self, addr: &model::Address, user_id: i64) -> Result<bool> {
+        let chat = addr
+            .chat_id
+            .as_i64()
+            .map(ChatId)
+            .ok_or_else(|| anyhow!("invalid telegram chat id: {}", addr.chat_id))?;
+        let member = self.bot.get_chat_member(chat, UserId(user_id as u64)).await?;
+        Ok(member.is_privileged())
+    }
+}
+
+fn markup_of(rows: &[Vec<Button>]) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(rows.iter().map(|row| {
+        row.iter()
+            .map(|b| InlineKeyboardButton::callback(b.label.clone(), b.data.clone()))
+    }))
 }
@@ -0,0 +1,53 @@
+// src/storage/memory.rs
+// 进程内后端：用于测试或不需要持久化的部署
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::KvBackend;
+
+pub struct MemoryBackend {
+    data: Mutex<BTreeMap<String, BTreeMap<String, String>>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self {
+            data: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl KvBackend for MemoryBackend {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn get(&self, ns: &str, key: &str) -> Result<Option<String>> {
+        let m = self.data.lock().unwrap();
+        Ok(m.get(ns).and_then(|t| t.get(key)).cloned())
+    }
+
+    async fn set(&self, ns: &str, key: &str, value: String) -> Result<()> {
+        let mut m = self.data.lock().unwrap();
+        m.entry(ns.to_string())
+            .or_default()
+            .insert(key.to_string(), value);
+        Ok(())
+    }
+
+    async fn delete(&self, ns: &str, key: &str) -> Result<bool> {
+        let mut m = self.data.lock().unwrap();
+        Ok(m.get_mut(ns).and_then(|t| t.remove(key)).is_some())
+    }
+
+    async fn scan(&self, ns: &str) -> Result<Vec<(String, String)>> {
+        let m = self.data.lock().unwrap();
+        Ok(m.get(ns)
+            .map(|t| t.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,56 @@
+// src/storage/migrations.rs
+// SQLite 表结构迁移，已应用的版本记录在 `PRAGMA user_version`
+use rusqlite::Connection;
+use tracing::info;
+
+/// (版本号, SQL)，版本号递增。只能在末尾追加，不要修改已发布的条目
+const MIGRATIONS: &[(i64, &str)] = &[(
+    1,
+    "CREATE TABLE kv (
+        ns         TEXT    NOT NULL,
+        key        TEXT    NOT NULL,
+        value      TEXT    NOT NULL,
+        updated_at INTEGER NOT NULL,
+        PRIMARY KEY (ns, key)
+    );",
+)];
+
+/// 每个版本在单独的事务中执行，失败时该版本整体回滚；返回执行后的版本号
+pub fn run(conn: &mut Connection) -> rusqlite::Result<i64> {
+    let mut current: i64 = conn.query_row("PRAGMA user_version", [], |r| r.get(0))?;
+    for (version, sql) in MIGRATIONS {
+        if *version <= current {
+            continue;
+        }
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", version)?;
+        tx.commit()?;
+        info!("storage migrated to v{version}");
+        current = *version;
+    }
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_apply_once() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let latest = MIGRATIONS.last().unwrap().0;
+        assert_eq!(run(&mut conn).unwrap(), latest);
+        assert_eq!(run(&mut conn).unwrap(), latest);
+
+        conn.execute("INSERT INTO kv VALUES ('ns', 'k', 'v', 0)", []).unwrap();
+        let n: i64 = conn.query_row("SELECT COUNT(*) FROM kv", [], |r| r.get(0)).unwrap();
+        assert_eq!(n, 1);
+    }
+
+    #[test]
+    fn versions_are_increasing() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(MIGRATIONS[0].0 >= 1);
+    }
+}
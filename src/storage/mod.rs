@@ -0,0 +1,200 @@
+// src/storage/mod.rs
+// 持久化存储：按命名空间划分的异步键值接口 + 基于 JSON 的类型化仓库
+pub mod memory;
+pub mod migrations;
+pub mod sqlite;
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::config::{StorageBackend, StorageConfig};
+
+/// 存储后端。键在命名空间（`ns`）内唯一，值为字符串
+#[async_trait]
+pub trait KvBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// 启动时执行未应用的迁移；没有表结构的后端无需实现
+    async fn migrate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// 按键读取单条；现有仓库启动时用 `scan` 整体读入，暂未单条读取
+    #[allow(dead_code)]
+    async fn get(&self, ns: &str, key: &str) -> Result<Option<String>>;
+
+    async fn set(&self, ns: &str, key: &str, value: String) -> Result<()>;
+
+    /// 返回是否确实删除了记录
+    async fn delete(&self, ns: &str, key: &str) -> Result<bool>;
+
+    /// 命名空间下的全部记录，按键排序
+    async fn scan(&self, ns: &str) -> Result<Vec<(String, String)>>;
+}
+
+/// 启动时打开一次，按命名空间分给各模块的 `Repository`；克隆开销很小
+#[derive(Clone)]
+pub struct Storage {
+    backend: Arc<dyn KvBackend>,
+}
+
+impl Storage {
+    pub fn open(cfg: &StorageConfig) -> Result<Self> {
+        let backend: Arc<dyn KvBackend> = match cfg.backend {
+            StorageBackend::Sqlite => Arc::new(
+                sqlite::SqliteBackend::open(&cfg.path)
+                    .with_context(|| format!("failed to open database {}", cfg.path))?,
+            ),
+            StorageBackend::Memory => Arc::new(memory::MemoryBackend::new()),
+        };
+        Ok(Self { backend })
+    }
+
+    /// 进程内存储，供测试使用；部署时用 `backend = "memory"`
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self {
+            backend: Arc::new(memory::MemoryBackend::new()),
+        }
+    }
+
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    pub async fn migrate(&self) -> Result<()> {
+        self.backend.migrate().await
+    }
+
+    /// 以 `ns` 为命名空间、值序列化为 JSON 的类型化仓库
+    pub fn repo<T>(&self, ns: &'static str) -> Repository<T> {
+        Repository {
+            backend: self.backend.clone(),
+            ns,
+            _marker: PhantomData,
+        }
+    }
+}
+
+pub struct Repository<T> {
+    backend: Arc<dyn KvBackend>,
+    ns: &'static str,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Serialize + DeserializeOwned> Repository<T> {
+    #[allow(dead_code)]
+    pub async fn get(&self, key: &str) -> Result<Option<T>> {
+        match self.backend.get(self.ns, key).await? {
+            Some(v) => Ok(Some(decode(self.ns, key, &v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn put(&self, key: &str, value: &T) -> Result<()> {
+        let v = serde_json::to_string(value)
+            .with_context(|| format!("failed to encode {}/{key}", self.ns))?;
+        self.backend.set(self.ns, key, v).await
+    }
+
+    pub async fn delete(&self, key: &str) -> Result<bool> {
+        self.backend.delete(self.ns, key).await
+    }
+
+    pub async fn all(&self) -> Result<Vec<(String, T)>> {
+        self.backend
+            .scan(self.ns)
+            .await?
+            .into_iter()
+            .map(|(k, v)| {
+                let t = decode(self.ns, &k, &v)?;
+                Ok((k, t))
+            })
+            .collect()
+    }
+}
+
+fn decode<T: DeserializeOwned>(ns: &str, key: &str, v: &str) -> Result<T> {
+    serde_json::from_str(v).with_context(|| format!("failed to decode {ns}/{key}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::config::{StorageBackend, StorageConfig};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Note {
+        text: String,
+        pinned: bool,
+    }
+
+    fn note(text: &str) -> Note {
+        Note {
+            text: text.to_string(),
+            pinned: false,
+        }
+    }
+
+    /// 两种后端共用的仓库行为
+    async fn check_repository(storage: &Storage) {
+        storage.migrate().await.unwrap();
+        let notes: Repository<Note> = storage.repo("notes");
+        let other: Repository<Note> = storage.repo("other");
+
+        assert_eq!(notes.get("a").await.unwrap(), None);
+        notes.put("b", &note("second")).await.unwrap();
+        notes.put("a", &note("first")).await.unwrap();
+        notes.put("a", &note("updated")).await.unwrap();
+        other.put("a", &note("elsewhere")).await.unwrap();
+
+        assert_eq!(notes.get("a").await.unwrap(), Some(note("updated")));
+        let keys: Vec<String> = notes.all().await.unwrap().into_iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, vec!["a", "b"]);
+
+        assert!(notes.delete("a").await.unwrap());
+        assert!(!notes.delete("a").await.unwrap());
+        assert_eq!(notes.get("a").await.unwrap(), None);
+        assert_eq!(other.get("a").await.unwrap(), Some(note("elsewhere")));
+
+        // 值不是合法 JSON 时报错而不是返回默认值
+        storage.backend.set("notes", "bad", "{".to_string()).await.unwrap();
+        assert!(notes.get("bad").await.is_err());
+        assert!(notes.all().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn memory_repository() {
+        let storage = Storage::in_memory();
+        assert_eq!(storage.backend_name(), "memory");
+        check_repository(&storage).await;
+    }
+
+    #[tokio::test]
+    async fn sqlite_repository_survives_reopen() {
+        let dir = std::env::temp_dir().join(format!("lukosbot-storage-{}", std::process::id()));
+        let cfg = StorageConfig {
+            backend: StorageBackend::Sqlite,
+            path: dir.join("test.db").to_string_lossy().into_owned(),
+        };
+
+        let storage = Storage::open(&cfg).unwrap();
+        check_repository(&storage).await;
+        drop(storage);
+
+        // 重新打开后迁移不会重复执行，数据仍在
+        let storage = Storage::open(&cfg).unwrap();
+        storage.migrate().await.unwrap();
+        let notes: Repository<Note> = storage.repo("notes");
+        assert_eq!(notes.get("b").await.unwrap(), Some(note("second")));
+        drop(storage);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,109 @@
+// src/storage/sqlite.rs
+// 嵌入式 SQLite 后端；rusqlite 是同步接口，查询放到阻塞线程池执行
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use rusqlite::{Connection, OptionalExtension, params};
+
+use super::{KvBackend, migrations};
+
+pub struct SqliteBackend {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteBackend {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        // WAL 下读写互不阻塞，也更不容易在异常退出时损坏
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    async fn call<R, F>(&self, f: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut Connection) -> rusqlite::Result<R> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        let r = tokio::task::spawn_blocking(move || f(&mut conn.lock().unwrap()))
+            .await
+            .context("storage task panicked")?;
+        Ok(r?)
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[async_trait]
+impl KvBackend for SqliteBackend {
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+
+    async fn migrate(&self) -> Result<()> {
+        self.call(|c| migrations::run(c).map(|_| ()))
+            .await
+            .context("failed to migrate database")
+    }
+
+    async fn get(&self, ns: &str, key: &str) -> Result<Option<String>> {
+        let (ns, key) = (ns.to_string(), key.to_string());
+        self.call(move |c| {
+            c.query_row(
+                "SELECT value FROM kv WHERE ns = ?1 AND key = ?2",
+                params![ns, key],
+                |r| r.get(0),
+            )
+            .optional()
+        })
+        .await
+    }
+
+    async fn set(&self, ns: &str, key: &str, value: String) -> Result<()> {
+        let (ns, key) = (ns.to_string(), key.to_string());
+        self.call(move |c| {
+            c.execute(
+                "INSERT INTO kv (ns, key, value, updated_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (ns, key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+                params![ns, key, value, now()],
+            )
+            .map(|_| ())
+        })
+        .await
+    }
+
+    async fn delete(&self, ns: &str, key: &str) -> Result<bool> {
+        let (ns, key) = (ns.to_string(), key.to_string());
+        self.call(move |c| {
+            c.execute("DELETE FROM kv WHERE ns = ?1 AND key = ?2", params![ns, key])
+                .map(|n| n > 0)
+        })
+        .await
+    }
+
+    async fn scan(&self, ns: &str) -> Result<Vec<(String, String)>> {
+        let ns = ns.to_string();
+        self.call(move |c| {
+            let mut stmt = c.prepare("SELECT key, value FROM kv WHERE ns = ?1 ORDER BY key")?;
+            let rows = stmt.query_map(params![ns], |r| Ok((r.get(0)?, r.get(1)?)))?;
+            rows.collect()
+        })
+        .await
+    }
+}